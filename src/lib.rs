@@ -1,20 +1,27 @@
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
 
+use std::borrow::Cow;
+use std::cmp::Reverse;
 use std::fmt::Display;
 use std::ops::Range;
 
 mod name_resolver;
+mod options;
 mod scope_index;
+mod scope_info;
 mod scope_name;
 mod source;
 mod swc;
 
 pub use name_resolver::NameResolver;
+pub use options::ParseOptions;
 pub use scope_index::{ScopeIndex, ScopeIndexError, ScopeLookupResult};
+pub use scope_info::{ScopeInfo, ScopeKind};
 pub use scope_name::{NameComponent, ScopeName};
 pub use source::{SourceContext, SourceContextError, SourcePosition};
 use swc_common::Spanned;
+use swc_ecma_visit::swc_ecma_ast as ast;
 
 /// The Scopes extracted from a piece of JS Code.
 pub type Scopes = Vec<(Range<u32>, Option<ScopeName>)>;
@@ -55,36 +62,291 @@ pub type Scopes = Vec<(Range<u32>, Option<ScopeName>)>;
 /// ];
 /// assert_eq!(scopes, expected);
 /// ```
-#[tracing::instrument(level = "trace", skip_all)]
 pub fn extract_scope_names(src: &str) -> Result<Scopes, ParseError> {
-    let mut scopes = swc::parse_with_swc(src).map_err(|e| ParseError { inner: e })?;
+    extract_scope_names_with_options(src, &ParseOptions::default())
+}
 
-    // filter out empty names
-    for scope in &mut scopes {
-        if let Some(ref name) = scope.1 {
-            if name.components.is_empty() {
-                scope.1 = None;
-            }
-        }
+/// Extracts function scopes from the given JS-like `src`, using the provided
+/// [`ParseOptions`].
+///
+/// See [`extract_scope_names`] for details about the returned scopes.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn extract_scope_names_with_options(
+    src: &str,
+    options: &ParseOptions,
+) -> Result<Scopes, ParseError> {
+    let mut scopes = vec![];
+
+    for_each_scope_name(src, options, |range, name, _info| {
+        scopes.push((range, name))
+    })?;
+
+    Ok(scopes)
+}
+
+/// Extracts function scopes from the given JS-like `src`, passing each of them
+/// to `on_scope` as soon as it is found.
+///
+/// Contrary to [`extract_scope_names_with_options`], this does not collect all
+/// the scopes into a Vec, so each scope can be processed and dropped right away.
+/// The scopes are passed to `on_scope` in the same order in which
+/// [`extract_scope_names_with_options`] returns them, together with a
+/// [`ScopeInfo`] containing the [`ScopeKind`] of the construct creating the
+/// scope. See [`extract_scope_names`] for details about the scopes.
+///
+/// # Examples
+///
+/// ```
+/// use js_source_scopes::{for_each_scope_name, ParseOptions, ScopeKind};
+///
+/// let src = "const arrowFnExpr = (a) => a; function namedFnDecl() {}";
+///
+/// let mut names = vec![];
+/// for_each_scope_name(src, &ParseOptions::default(), |_range, name, info| {
+///     names.push((name.unwrap().to_string(), info.kind));
+/// })
+/// .unwrap();
+///
+/// let expected = [
+///     (String::from("arrowFnExpr"), ScopeKind::Arrow),
+///     (String::from("namedFnDecl"), ScopeKind::Function),
+/// ];
+/// assert_eq!(names, expected);
+/// ```
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn for_each_scope_name<F>(
+    src: &str,
+    options: &ParseOptions,
+    mut on_scope: F,
+) -> Result<(), ParseError>
+where
+    F: FnMut(Range<u32>, Option<ScopeName>, ScopeInfo),
+{
+    swc::parse_with_swc(src, 0, options, &mut |range, name, info| {
+        on_scope(range, normalize_name(name), info)
+    })
+    .map_err(ParseError::syntax)
+}
+
+/// Extracts function scopes from the given JS-like `src`, which is located at
+/// `base_offset` inside of a larger source file.
+///
+/// The `src` has to be parseable on its own, like a single module or script
+/// that was concatenated into a bundle. All the returned ranges, including the
+/// ranges of the [`NameComponent`]s, are relative to the start of the larger
+/// file, as if the whole file was passed to [`extract_scope_names`]. This allows
+/// re-extracting the scopes of a changed region only.
+///
+/// Returns an `Err` if the end of `src` would exceed the range of a `u32`.
+///
+/// # Examples
+///
+/// ```
+/// let prefix = "const unchanged = () => {};\n";
+/// let src = "function changed() {}";
+///
+/// let scopes = js_source_scopes::extract_scope_names_at(src, prefix.len() as u32).unwrap();
+/// assert_eq!(scopes[0].0, 28..49);
+/// ```
+pub fn extract_scope_names_at(src: &str, base_offset: u32) -> Result<Scopes, ParseError> {
+    extract_scope_names_at_with_options(src, base_offset, &ParseOptions::default())
+}
+
+/// Extracts function scopes from the given JS-like `src`, which is located at
+/// `base_offset` inside of a larger source file, using the provided [`ParseOptions`].
+///
+/// The `options` should match the ones used to parse the whole file, so that
+/// the re-extracted names line up with the ones of a full-file parse.
+/// See [`extract_scope_names_at`] for details about the returned scopes.
+pub fn extract_scope_names_at_with_options(
+    src: &str,
+    base_offset: u32,
+    options: &ParseOptions,
+) -> Result<Scopes, ParseError> {
+    let fits = u32::try_from(src.len())
+        .ok()
+        .and_then(|len| base_offset.checked_add(len))
+        .is_some();
+    if !fits {
+        return Err(ParseError {
+            inner: ParseErrorInner::OffsetOverflow(base_offset),
+        });
     }
 
+    let mut scopes = vec![];
+
+    swc::parse_with_swc(src, base_offset, options, &mut |range, name, _info| {
+        scopes.push((range, normalize_name(name)))
+    })
+    .map_err(ParseError::syntax)?;
+
     Ok(scopes)
 }
 
+/// Extracts a flat outline of all the named scopes in the given JS-like `src`.
+///
+/// Contrary to [`extract_scope_names`], scopes without a name are skipped, and
+/// names are rendered to a `String`. The returned Vec is sorted by start offset,
+/// with outer scopes coming before the scopes nested inside of them, and does
+/// not contain duplicates.
+///
+/// This works purely on the level of the given `src`, and does not resolve
+/// minified names to their original names.
+///
+/// # Examples
+///
+/// ```
+/// let src = "class Foo { bar() {} } function baz() {}";
+///
+/// let outline = js_source_scopes::extract_named_scopes(src).unwrap();
+/// let names: Vec<_> = outline.iter().map(|(_, name)| name.as_str()).collect();
+/// assert_eq!(names, ["new Foo", "Foo.bar", "baz"]);
+/// ```
+pub fn extract_named_scopes(src: &str) -> Result<Vec<(Range<u32>, String)>, ParseError> {
+    extract_named_scopes_with_options(src, &ParseOptions::default())
+}
+
+/// Extracts a flat outline of all the named scopes in the given JS-like `src`,
+/// using the provided [`ParseOptions`].
+///
+/// See [`extract_named_scopes`] for details about the returned outline.
+pub fn extract_named_scopes_with_options(
+    src: &str,
+    options: &ParseOptions,
+) -> Result<Vec<(Range<u32>, String)>, ParseError> {
+    let scopes = extract_scope_names_with_options(src, options)?;
+
+    let mut named: Vec<_> = scopes
+        .into_iter()
+        .filter_map(|(range, name)| Some((range, name?.to_string())))
+        .collect();
+    named.sort_by(|(a_range, a_name), (b_range, b_name)| {
+        (a_range.start, Reverse(a_range.end), a_name).cmp(&(
+            b_range.start,
+            Reverse(b_range.end),
+            b_name,
+        ))
+    });
+    named.dedup();
+
+    Ok(named)
+}
+
+/// Extracts the aliases of renamed exports from the given JS-like `src`.
+///
+/// For each `export { local as exported }` specifier, this returns the pair of
+/// the `local` and `exported` names. The scope names returned by
+/// [`extract_scope_names`] use the local name of a declaration, whereas
+/// consumers of the module may refer to it by its exported alias.
+/// Specifiers that keep their name, and re-exports from other modules are skipped.
+///
+/// # Examples
+///
+/// ```
+/// let src = "function foo() {} export { foo as bar };";
+///
+/// let aliases = js_source_scopes::extract_export_aliases(src).unwrap();
+/// assert_eq!(aliases, [(String::from("foo"), String::from("bar"))]);
+/// ```
+pub fn extract_export_aliases(src: &str) -> Result<Vec<(String, String)>, ParseError> {
+    extract_export_aliases_with_options(src, &ParseOptions::default())
+}
+
+/// Extracts the aliases of renamed exports from the given JS-like `src`, using
+/// the provided [`ParseOptions`].
+///
+/// See [`extract_export_aliases`] for details about the returned aliases.
+pub fn extract_export_aliases_with_options(
+    src: &str,
+    options: &ParseOptions,
+) -> Result<Vec<(String, String)>, ParseError> {
+    swc::parse_export_aliases(src, options).map_err(ParseError::syntax)
+}
+
+/// Extracts function scopes from an already parsed SWC [`Module`](ast::Module).
+///
+/// This allows extracting scopes from a module that was parsed with custom
+/// syntax or comments handling, without parsing the source again.
+/// The byte ranges of the returned scopes correspond to the spans of the parsed
+/// module. See [`extract_scope_names`] for details about the returned scopes.
+pub fn collect_scopes_from_module(module: &ast::Module, options: &ParseOptions) -> Scopes {
+    let mut scopes = vec![];
+
+    swc::visit_scopes(module, options, None, &mut |range, name, _info| {
+        scopes.push((range, normalize_name(name)))
+    });
+
+    scopes
+}
+
+/// Extracts function scopes from an already parsed SWC [`Script`](ast::Script).
+///
+/// See [`collect_scopes_from_module`] for details.
+pub fn collect_scopes_from_script(script: &ast::Script, options: &ParseOptions) -> Scopes {
+    let mut scopes = vec![];
+
+    swc::visit_scopes(script, options, None, &mut |range, name, _info| {
+        scopes.push((range, normalize_name(name)))
+    });
+
+    scopes
+}
+
+/// [Normalizes](ScopeName::normalize) the scope `name`, returning `None` if it is empty.
+fn normalize_name(mut name: ScopeName) -> Option<ScopeName> {
+    name.normalize();
+
+    (!name.components.is_empty()).then_some(name)
+}
+
 /// An error parsing the JS Source provided to [`extract_scope_names`].
 #[derive(Debug)]
 pub struct ParseError {
-    inner: swc::ParseError,
+    inner: ParseErrorInner,
+}
+
+#[derive(Debug)]
+enum ParseErrorInner {
+    Syntax(swc::ParseError),
+    /// The source passed to [`extract_scope_names_at`] exceeds the range of a
+    /// `u32` when starting at the contained base offset.
+    OffsetOverflow(u32),
+}
+
+impl ParseError {
+    fn syntax(err: swc::ParseError) -> Self {
+        Self {
+            inner: ParseErrorInner::Syntax(err),
+        }
+    }
+
+    /// The byte range inside the source at which the error occurred.
+    pub fn range(&self) -> Range<u32> {
+        match &self.inner {
+            ParseErrorInner::Syntax(err) => swc::convert_span(err.span()),
+            ParseErrorInner::OffsetOverflow(base_offset) => *base_offset..u32::MAX,
+        }
+    }
+
+    /// A human readable message describing the error.
+    pub fn message(&self) -> Cow<'static, str> {
+        match &self.inner {
+            ParseErrorInner::Syntax(err) => err.kind().msg(),
+            ParseErrorInner::OffsetOverflow(_) => {
+                Cow::Borrowed("source exceeds the u32 offset range")
+            }
+        }
+    }
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let span = self.inner.span();
+        let range = self.range();
         f.write_fmt(format_args!(
             "{}:{}:{}",
-            span.lo.0,
-            span.hi.0,
-            self.inner.kind().msg()
+            range.start,
+            range.end,
+            self.message()
         ))
     }
 }
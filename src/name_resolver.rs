@@ -19,7 +19,8 @@ impl<'a, T: AsRef<str>> NameResolver<'a, T> {
     ///
     /// This tries to resolve each [`NameComponent`] by looking up its source
     /// range in the [`DecodedMap`], using the token's `name` (as defined in the
-    /// sourcemap `names`) when possible.
+    /// sourcemap `names`) when possible. This includes the member properties
+    /// of a name, so a mangled `obj.a` can be resolved to `obj.handler`.
     pub fn resolve_name(&self, name: &ScopeName) -> String {
         name.components()
             .map(|c| self.try_map_token(c).unwrap_or_else(|| c.text()))
@@ -0,0 +1,98 @@
+use swc_ecma_parser::Syntax;
+use swc_ecma_visit::swc_ecma_ast::EsVersion;
+
+/// Options controlling how scopes are extracted from JS source and how their
+/// names are inferred.
+///
+/// New options may be added in the future, so this can only be created via
+/// its [`Default`] implementation, and then be adjusted as needed.
+///
+/// # Examples
+///
+/// ```
+/// use js_source_scopes::{extract_scope_names_with_options, ParseOptions};
+///
+/// let src = "exports.handler = function () {};";
+///
+/// let mut options = ParseOptions::default();
+/// options.strip_commonjs_exports = false;
+///
+/// let scopes = extract_scope_names_with_options(src, &options).unwrap();
+/// assert_eq!(scopes[0].1.as_ref().unwrap().to_string(), "exports.handler");
+/// ```
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// The [`Syntax`] used to parse the source.
+    ///
+    /// This can be used to enable proposals like decorators or import attributes.
+    /// Defaults to plain ECMAScript.
+    pub syntax: Syntax,
+
+    /// The ECMAScript version of the parsed source.
+    ///
+    /// Defaults to the default of SWC.
+    pub target: EsVersion,
+
+    /// Drop a leading `module.exports` or `exports` from names inferred from
+    /// CommonJS export assignments.
+    ///
+    /// With this enabled, `exports.foo = function () {}` is named `foo`, and
+    /// `module.exports = function () {}` is named `default`.
+    ///
+    /// Defaults to `true`.
+    pub strip_commonjs_exports: bool,
+
+    /// Infer names for anonymous callbacks passed to well-known functions.
+    ///
+    /// This is a heuristic, which names for example the callback in
+    /// `el.addEventListener("click", function () {})` as `<click handler>`,
+    /// and the one in `items.map(() => {})` as `map callback`. Callbacks passed
+    /// to other methods are named after the method as well, so the one in
+    /// `foo.bar().baz(() => {})` is named `baz callback`. Callbacks passed to
+    /// `setTimeout`, `setInterval` and `requestAnimationFrame` are named
+    /// `setTimeout callback` and so on.
+    /// With decorators enabled in the [`syntax`](Self::syntax), functions
+    /// passed to a class decorator are named like `@connect arg in C` for
+    /// `@connect((state) => state.foo) class C {}`.
+    /// Accessors defined with `Object.defineProperty(X, "foo", { get() {} })`
+    /// are named like `get X.foo`.
+    /// A name inferred from the variable that the call result is assigned to
+    /// takes precedence.
+    ///
+    /// Defaults to `false`.
+    pub infer_callback_names: bool,
+
+    /// Name functions that are directly returned from another function after
+    /// that factory function.
+    ///
+    /// This is a heuristic, which names for example the returned function in
+    /// `function makeHandler() { return () => {}; }` as `makeHandler.<return>`.
+    /// Functions returned from the concise body of an arrow function are always
+    /// named this way, regardless of this option, so the scopes of the curried
+    /// `const add = (a) => (b) => a + b` are named `add` and `add.<return>`.
+    ///
+    /// Defaults to `false`.
+    pub infer_returned_function_names: bool,
+
+    /// The marker prepended to the names of class scopes.
+    ///
+    /// With the default, the scope of `class Foo {}` is named `new Foo`, as it
+    /// includes the constructor. Use an empty string to name it just `Foo`.
+    ///
+    /// Defaults to `"new "`.
+    pub class_prefix: &'static str,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            syntax: Syntax::default(),
+            target: EsVersion::default(),
+            strip_commonjs_exports: true,
+            infer_callback_names: false,
+            infer_returned_function_names: false,
+            class_prefix: "new ",
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::cmp::Reverse;
 use std::ops::Range;
 
 use indexmap::IndexSet;
@@ -30,16 +31,26 @@ pub struct ScopeIndex {
     names: IndexSet<String>,
     /// Offset -> Index into `names` (or `u32::MAX` for `None`)
     ranges: Vec<(u32, u32)>,
+    /// The original scope ranges, sorted by start offset, with their index into `names`
+    /// and the index of their parent scope within `scopes`.
+    scopes: Vec<(Range<u32>, u32, Option<u32>)>,
 }
 
 impl ScopeIndex {
     /// Creates a new Scope index from the given list of Scopes.
+    ///
+    /// The scopes can be given in any order. They are sorted by their start
+    /// offset, and scopes starting at the same offset by their end offset in
+    /// descending order, so that outer scopes always come before the scopes
+    /// nested inside of them. Scopes with identical ranges keep their order.
     #[tracing::instrument(level = "trace", name = "ScopeIndex::new", skip_all)]
     pub fn new(mut scopes: Vec<(Range<u32>, Option<String>)>) -> Result<Self, ScopeIndexError> {
         let mut names = IndexSet::new();
         let mut ranges = vec![];
+        let mut original_scopes: Vec<(Range<u32>, u32, Option<u32>)> =
+            Vec::with_capacity(scopes.len());
 
-        scopes.sort_by_key(|s| s.0.start);
+        scopes.sort_by_key(|s| (s.0.start, Reverse(s.0.end)));
 
         let needs_zero = scopes.first().map(|s| s.0.start != 0).unwrap_or(false);
         if needs_zero {
@@ -47,20 +58,31 @@ impl ScopeIndex {
         }
 
         let mut stack: Vec<(Range<u32>, u32)> = vec![];
+        // indices into `original_scopes` of all the scopes containing the current one
+        let mut parents: Vec<usize> = vec![];
 
         for (range, name) in scopes {
             unwind_scope_stack(&mut ranges, &mut stack, range.clone())?;
 
             let name_idx = match name {
-                Some(name) => names
-                    .insert_full(name)
-                    .0
-                    .try_into()
-                    .map_err(|_| ScopeIndexError(()))?,
+                Some(name) => to_name_idx(names.insert_full(name).0)?,
                 None => ANONYMOUS_SCOPE_SENTINEL,
             };
 
+            while let Some(&parent) = parents.last() {
+                if original_scopes[parent].0.end > range.start {
+                    break;
+                }
+                parents.pop();
+            }
+            let parent = parents
+                .last()
+                .map(|&parent| parent.try_into().map_err(|_| ScopeIndexError(())))
+                .transpose()?;
+
             ranges.push((range.start, name_idx));
+            parents.push(original_scopes.len());
+            original_scopes.push((range.clone(), name_idx, parent));
 
             if let Some(last) = stack.last() {
                 if last.0.end == range.end {
@@ -80,11 +102,20 @@ impl ScopeIndex {
             ranges.push((last.0.end, name_idx));
         }
 
-        Ok(Self { names, ranges })
+        Ok(Self {
+            names,
+            ranges,
+            scopes: original_scopes,
+        })
     }
 
     /// Looks up the scope corresponding to the given `offset`.
-    pub fn lookup(&self, offset: u32) -> ScopeLookupResult {
+    ///
+    /// Scope ranges are half-open: The `start` offset of a scope is part of
+    /// that scope, whereas its `end` offset already belongs to the enclosing
+    /// parent scope. Offsets before the first scope, and at or after the end of
+    /// the last scope resolve to [`ScopeLookupResult::Unknown`].
+    pub fn lookup(&self, offset: u32) -> ScopeLookupResult<'_> {
         let range_idx = match self.ranges.binary_search_by_key(&offset, |r| r.0) {
             Ok(idx) => idx,
             Err(0) => 0, // this is pretty much unreachable since the first offset is 0
@@ -99,7 +130,50 @@ impl ScopeIndex {
         self.resolve_name(name_idx)
     }
 
-    fn resolve_name(&self, name_idx: u32) -> ScopeLookupResult {
+    /// Looks up the chain of all scopes containing the given `offset`.
+    ///
+    /// The scopes are ordered from the outermost to the innermost one, so the
+    /// last entry corresponds to the result of [`lookup`](Self::lookup).
+    /// Scopes with identical ranges are returned in the order they were given
+    /// to [`ScopeIndex::new`]. If the `offset` is not contained in any scope,
+    /// the returned chain is empty.
+    pub fn lookup_nested(&self, offset: u32) -> Vec<ScopeLookupResult<'_>> {
+        let candidates = self.scopes.partition_point(|s| s.0.start <= offset);
+
+        // All the scopes containing `offset` are parents of the last scope
+        // starting before it, so we walk up from there.
+        let mut chain = vec![];
+        let mut next = candidates.checked_sub(1);
+        while let Some(scope_idx) = next {
+            let (range, name_idx, parent) = &self.scopes[scope_idx];
+            if range.contains(&offset) {
+                chain.push(self.resolve_name(*name_idx));
+            }
+            next = parent.map(|parent| parent as usize);
+        }
+
+        chain.reverse();
+        chain
+    }
+
+    /// Looks up all scopes that intersect the given `range`.
+    ///
+    /// The scopes are returned together with their original range, ordered by
+    /// their start offset, so outer scopes come before the scopes nested inside
+    /// of them. Just like in [`lookup`](Self::lookup), scope ranges are
+    /// half-open. An empty `range` is treated like a lookup of its start offset.
+    pub fn lookup_range(&self, range: Range<u32>) -> Vec<(Range<u32>, ScopeLookupResult<'_>)> {
+        let end = range.end.max(range.start.saturating_add(1));
+        let candidates = self.scopes.partition_point(|s| s.0.start < end);
+
+        self.scopes[..candidates]
+            .iter()
+            .filter(|s| s.0.end > range.start)
+            .map(|s| (s.0.clone(), self.resolve_name(s.1)))
+            .collect()
+    }
+
+    fn resolve_name(&self, name_idx: u32) -> ScopeLookupResult<'_> {
         if name_idx == GLOBAL_SCOPE_SENTINEL {
             ScopeLookupResult::Unknown
         } else if name_idx == ANONYMOUS_SCOPE_SENTINEL {
@@ -116,13 +190,16 @@ impl ScopeIndex {
     /// offsets.
     ///
     /// Scopes are returned in order of their starting offsets.
-    pub fn iter(&self) -> impl Iterator<Item = (u32, ScopeLookupResult)> {
+    pub fn iter(&self) -> impl Iterator<Item = (u32, ScopeLookupResult<'_>)> {
         self.ranges.iter().map(|r| (r.0, self.resolve_name(r.1)))
     }
 }
 
 /// The Result of a Scope lookup.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+///
+/// Named results borrow the name from the [`ScopeIndex`] they were looked up
+/// in, and compare and hash by that name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ScopeLookupResult<'data> {
     /// A named function scope.
     NamedScope(&'data str),
@@ -161,6 +238,17 @@ fn unwind_scope_stack(
     Ok(())
 }
 
+/// Converts an index into the list of names to the `u32` stored in the index.
+///
+/// Indices colliding with the sentinels would resolve to the wrong scope, so
+/// they result in an error.
+fn to_name_idx(idx: usize) -> Result<u32, ScopeIndexError> {
+    u32::try_from(idx)
+        .ok()
+        .filter(|idx| *idx < ANONYMOUS_SCOPE_SENTINEL)
+        .ok_or(ScopeIndexError(()))
+}
+
 pub(crate) const GLOBAL_SCOPE_SENTINEL: u32 = u32::MAX;
 pub(crate) const ANONYMOUS_SCOPE_SENTINEL: u32 = u32::MAX - 1;
 
@@ -186,6 +274,18 @@ mod tests {
         assert!(ScopeIndex::new(scopes).is_err());
     }
 
+    #[test]
+    fn name_idx_sentinels() {
+        assert_eq!(to_name_idx(0).unwrap(), 0);
+        assert_eq!(
+            to_name_idx(u32::MAX as usize - 2).unwrap(),
+            ANONYMOUS_SCOPE_SENTINEL - 1
+        );
+        assert!(to_name_idx(ANONYMOUS_SCOPE_SENTINEL as usize).is_err());
+        assert!(to_name_idx(GLOBAL_SCOPE_SENTINEL as usize).is_err());
+        assert!(to_name_idx(usize::MAX).is_err());
+    }
+
     #[test]
     fn scope_index() {
         let scopes = vec![
@@ -206,4 +306,178 @@ mod tests {
         assert_eq!(idx.lookup(30), ScopeLookupResult::AnonymousScope);
         assert_eq!(idx.lookup(50), ScopeLookupResult::Unknown);
     }
+
+    #[test]
+    fn lookup_boundaries() {
+        let scopes = vec![
+            (10..40, Some(String::from("outer"))),
+            (20..30, Some(String::from("inner"))),
+        ];
+
+        let idx = ScopeIndex::new(scopes).unwrap();
+
+        // before the first scope
+        assert_eq!(idx.lookup(0), ScopeLookupResult::Unknown);
+        assert_eq!(idx.lookup(9), ScopeLookupResult::Unknown);
+
+        // the start is inclusive, the end exclusive
+        assert_eq!(idx.lookup(10), ScopeLookupResult::NamedScope("outer"));
+        assert_eq!(idx.lookup(19), ScopeLookupResult::NamedScope("outer"));
+        assert_eq!(idx.lookup(20), ScopeLookupResult::NamedScope("inner"));
+        assert_eq!(idx.lookup(29), ScopeLookupResult::NamedScope("inner"));
+        assert_eq!(idx.lookup(30), ScopeLookupResult::NamedScope("outer"));
+        assert_eq!(idx.lookup(39), ScopeLookupResult::NamedScope("outer"));
+
+        // after the last scope
+        assert_eq!(idx.lookup(40), ScopeLookupResult::Unknown);
+        assert_eq!(idx.lookup(u32::MAX), ScopeLookupResult::Unknown);
+    }
+
+    #[test]
+    fn lookup_nested() {
+        let scopes = vec![
+            (0..100, Some(String::from("outer"))),
+            (10..50, None),
+            (20..30, Some(String::from("inner"))),
+            (60..70, Some(String::from("sibling"))),
+        ];
+
+        let idx = ScopeIndex::new(scopes).unwrap();
+
+        use ScopeLookupResult::*;
+        assert_eq!(
+            idx.lookup_nested(25),
+            [NamedScope("outer"), AnonymousScope, NamedScope("inner")]
+        );
+        assert_eq!(idx.lookup_nested(30), [NamedScope("outer"), AnonymousScope]);
+        assert_eq!(
+            idx.lookup_nested(65),
+            [NamedScope("outer"), NamedScope("sibling")]
+        );
+        assert!(idx.lookup_nested(100).is_empty());
+    }
+
+    #[test]
+    fn lookup_nested_shared_bounds() {
+        let scopes = vec![
+            (10..40, Some(String::from("outer"))),
+            (30..40, Some(String::from("tail"))),
+            (10..20, Some(String::from("inner"))),
+            (10..20, Some(String::from("inner2"))),
+            (15..15, Some(String::from("empty"))),
+            (50..60, None),
+        ];
+
+        let idx = ScopeIndex::new(scopes).unwrap();
+
+        use ScopeLookupResult::*;
+        let inner_chain = [
+            NamedScope("outer"),
+            NamedScope("inner"),
+            NamedScope("inner2"),
+        ];
+        assert_eq!(idx.lookup_nested(10), inner_chain);
+        assert_eq!(idx.lookup_nested(17), inner_chain);
+        assert_eq!(idx.lookup_nested(25), [NamedScope("outer")]);
+        assert_eq!(
+            idx.lookup_nested(35),
+            [NamedScope("outer"), NamedScope("tail")]
+        );
+        assert!(idx.lookup_nested(45).is_empty());
+        assert_eq!(idx.lookup_nested(55), [AnonymousScope]);
+    }
+
+    #[test]
+    fn lookup_range() {
+        let scopes = vec![
+            (0..100, Some(String::from("outer"))),
+            (10..50, None),
+            (20..30, Some(String::from("inner"))),
+            (60..70, Some(String::from("sibling"))),
+        ];
+
+        let idx = ScopeIndex::new(scopes).unwrap();
+
+        use ScopeLookupResult::*;
+        assert_eq!(
+            idx.lookup_range(25..65),
+            [
+                (0..100, NamedScope("outer")),
+                (10..50, AnonymousScope),
+                (20..30, NamedScope("inner")),
+                (60..70, NamedScope("sibling")),
+            ]
+        );
+        // the ends of the ranges are exclusive
+        assert_eq!(
+            idx.lookup_range(30..60),
+            [(0..100, NamedScope("outer")), (10..50, AnonymousScope)]
+        );
+        // a zero-length range behaves like a point lookup
+        assert_eq!(
+            idx.lookup_range(25..25),
+            [
+                (0..100, NamedScope("outer")),
+                (10..50, AnonymousScope),
+                (20..30, NamedScope("inner")),
+            ]
+        );
+        // a range enclosing all scopes
+        assert_eq!(idx.lookup_range(0..u32::MAX).len(), 4);
+        assert!(idx.lookup_range(100..200).is_empty());
+    }
+
+    #[test]
+    fn lookup_results_dedup() {
+        let scopes = vec![
+            (0..10, Some(String::from("same"))),
+            (20..30, Some(String::from("same"))),
+            (40..50, None),
+            (60..70, None),
+        ];
+
+        let idx = ScopeIndex::new(scopes).unwrap();
+
+        let results: std::collections::HashSet<_> = [5, 15, 25, 45, 65, 75]
+            .map(|offset| idx.lookup(offset))
+            .into();
+        assert_eq!(results.len(), 3);
+        assert!(results.contains(&ScopeLookupResult::NamedScope("same")));
+        assert!(results.contains(&ScopeLookupResult::AnonymousScope));
+        assert!(results.contains(&ScopeLookupResult::Unknown));
+    }
+
+    #[test]
+    fn sort_same_start() {
+        let scopes = vec![
+            (10..20, Some(String::from("inner"))),
+            (30..40, Some(String::from("sibling"))),
+            (10..40, Some(String::from("outer"))),
+            (10..20, Some(String::from("inner2"))),
+        ];
+
+        let idx = ScopeIndex::new(scopes).unwrap();
+
+        use ScopeLookupResult::*;
+        assert_eq!(
+            idx.lookup_range(0..50),
+            [
+                (10..40, NamedScope("outer")),
+                (10..20, NamedScope("inner")),
+                (10..20, NamedScope("inner2")),
+                (30..40, NamedScope("sibling")),
+            ]
+        );
+        assert_eq!(idx.lookup(15), NamedScope("inner2"));
+        assert_eq!(idx.lookup(25), NamedScope("outer"));
+        assert_eq!(idx.lookup(35), NamedScope("sibling"));
+    }
+
+    #[test]
+    fn lookup_empty() {
+        let idx = ScopeIndex::new(vec![]).unwrap();
+
+        assert_eq!(idx.lookup(0), ScopeLookupResult::Unknown);
+        assert_eq!(idx.lookup(10), ScopeLookupResult::Unknown);
+    }
 }
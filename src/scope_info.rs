@@ -0,0 +1,49 @@
+/// The kind of syntax construct that creates a function scope.
+///
+/// The kind is passed to the callback of
+/// [`for_each_scope_name`](crate::for_each_scope_name) as part of the
+/// [`ScopeInfo`]. It is not part of the [`Scopes`](crate::Scopes) returned by
+/// [`extract_scope_names`](crate::extract_scope_names), so the type of those
+/// stays unchanged for existing users.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ScopeKind {
+    /// A function declaration or expression:
+    /// `function name() {}`
+    Function,
+    /// An arrow function:
+    /// `() => {}`
+    Arrow,
+    /// A method of a class or object literal:
+    /// `{ name() {} }`
+    Method,
+    /// A class, including its constructor and field initializers:
+    /// `class Name {}`
+    Class,
+    /// A getter of a class or object literal:
+    /// `{ get name() {} }`
+    Getter,
+    /// A setter of a class or object literal:
+    /// `{ set name(value) {} }`
+    Setter,
+    /// A static initialization block of a class:
+    /// `class Name { static {} }`
+    StaticBlock,
+}
+
+/// Information about a function scope, besides its range and name.
+///
+/// This is passed to the callback of
+/// [`for_each_scope_name`](crate::for_each_scope_name) for each scope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ScopeInfo {
+    /// The kind of syntax construct that creates the scope.
+    pub kind: ScopeKind,
+    /// Whether the scope is directly preceded by a bundler pragma comment.
+    ///
+    /// The recognized pragmas are `/*#__PURE__*/` and `/*#__NO_SIDE_EFFECTS__*/`,
+    /// both also with a leading `@` instead of `#`. Such pragmas are usually kept
+    /// by minifiers, and mark functions that can be safely removed if unused.
+    pub has_leading_pragma: bool,
+}
@@ -24,6 +24,195 @@ impl ScopeName {
     pub fn components(&self) -> impl Iterator<Item = &NameComponent> + '_ {
         self.components.iter()
     }
+
+    /// The text of the last identifier in this name.
+    ///
+    /// For example, this returns `method` for `get a.b.method`, which can be
+    /// used to group names by their function name only. Returns `None` if the
+    /// name only consists of synthetic components, like `default`.
+    pub fn leaf(&self) -> Option<&str> {
+        self.components
+            .iter()
+            .rev()
+            .find(|c| c.range().is_some())
+            .map(|c| c.text())
+    }
+
+    /// The textual name, joined from all the individual components.
+    ///
+    /// This matches the [`Display`] output, but avoids allocating when the
+    /// name consists of a single component.
+    pub fn as_components_str(&self) -> Cow<'_, str> {
+        match self.components.len() {
+            0 => Cow::Borrowed(""),
+            1 => Cow::Borrowed(self.components[0].text()),
+            _ => Cow::Owned(self.components().map(|c| c.text()).collect()),
+        }
+    }
+
+    /// Consumes this name, returning the owned text of each of its components.
+    ///
+    /// Joining the returned strings matches the [`Display`] output.
+    /// Synthetic components that already own their text are moved out without
+    /// copying them.
+    pub fn into_component_strings(self) -> Vec<String> {
+        self.components
+            .into_iter()
+            .map(|c| match c.inner {
+                NameComponentInner::Interpolation(s) | NameComponentInner::Key(s) => s.into_owned(),
+                NameComponentInner::SourceIdentifierToken(t) => t.sym.to_string(),
+            })
+            .collect()
+    }
+
+    /// Normalizes the synthetic components of this name.
+    ///
+    /// Adjacent interpolations are merged into a single component, a `.`
+    /// separator directly following another one is dropped, and a leading `.`
+    /// separator is removed. That way, a name rendered as `.Foo..bar` before
+    /// normalization is rendered as `Foo.bar` afterwards.
+    pub fn normalize(&mut self) {
+        let mut components = VecDeque::with_capacity(self.components.len());
+
+        for component in self.components.drain(..) {
+            let mut text = match component.inner {
+                NameComponentInner::Interpolation(text) => text,
+                _ => {
+                    components.push_back(component);
+                    continue;
+                }
+            };
+
+            let at_start = components.is_empty();
+            let prev = match components.back_mut() {
+                Some(NameComponent {
+                    inner: NameComponentInner::Interpolation(prev),
+                }) => Some(prev),
+                _ => None,
+            };
+            let ends_with_sep = match &prev {
+                Some(prev) => prev.ends_with('.'),
+                None => at_start,
+            };
+            if ends_with_sep {
+                text = match text {
+                    Cow::Borrowed(s) => Cow::Borrowed(s.trim_start_matches('.')),
+                    Cow::Owned(s) => Cow::Owned(s.trim_start_matches('.').to_owned()),
+                };
+            }
+
+            match prev {
+                Some(prev) => prev.to_mut().push_str(&text),
+                None if !text.is_empty() => components.push_back(NameComponent::interp(text)),
+                None => {}
+            }
+        }
+
+        self.components = components;
+    }
+
+    /// Compares this name to `other`, ignoring how the synthetic components
+    /// were constructed.
+    ///
+    /// Identifiers and bracketed keys like `[0]` are compared by their exact
+    /// text, ignoring their ranges.
+    /// Adjacent synthetic components are compared as a whole, after merging them
+    /// and dropping redundant `.` separators, the same way that
+    /// [`normalize`](Self::normalize) does. Names that differ only in how their
+    /// markers and separators were split into components are thus equal, whereas
+    /// `Foo.bar` and `Foo#bar`, or a synthetic `default` and a `default`
+    /// identifier are not.
+    pub fn semantic_eq(&self, other: &ScopeName) -> bool {
+        #[derive(PartialEq)]
+        enum Part<'a> {
+            Ident(&'a str),
+            Key(&'a str),
+            Marker(String),
+        }
+
+        fn parts(name: &ScopeName) -> Vec<Part<'_>> {
+            let mut parts = vec![];
+            let mut marker = String::new();
+
+            for c in name.components() {
+                match c.inner {
+                    NameComponentInner::Interpolation(ref text) => {
+                        let after_sep = match marker.chars().last() {
+                            Some(prev) => prev == '.',
+                            None => parts.is_empty(),
+                        };
+                        if after_sep {
+                            marker.push_str(text.trim_start_matches('.'));
+                        } else {
+                            marker.push_str(text);
+                        }
+                    }
+                    NameComponentInner::SourceIdentifierToken(ref ident) => {
+                        if !marker.is_empty() {
+                            parts.push(Part::Marker(std::mem::take(&mut marker)));
+                        }
+                        parts.push(Part::Ident(&ident.sym));
+                    }
+                    NameComponentInner::Key(ref key) => {
+                        if !marker.is_empty() {
+                            parts.push(Part::Marker(std::mem::take(&mut marker)));
+                        }
+                        parts.push(Part::Key(key));
+                    }
+                }
+            }
+            if !marker.is_empty() {
+                parts.push(Part::Marker(marker));
+            }
+
+            parts
+        }
+
+        parts(self) == parts(other)
+    }
+
+    /// Renders this name, keeping only the last `max_components` identifiers.
+    ///
+    /// The elided leading part of the name is replaced with an ellipsis (`…`),
+    /// while a leading marker like `new ` or `get ` is preserved. For example,
+    /// `get a.b.c.method` truncated to 2 identifiers is rendered as `get ….c.method`.
+    /// Names with at most `max_components` identifiers are rendered in full.
+    pub fn to_truncated_string(&self, max_components: usize) -> String {
+        let idents: Vec<_> = self
+            .components()
+            .enumerate()
+            .filter(|(_, c)| c.range().is_some())
+            .map(|(idx, _)| idx)
+            .collect();
+
+        if idents.len() <= max_components {
+            return self.to_string();
+        }
+
+        // a leading marker like `new ` can be merged with other synthetic
+        // components by `normalize`, so only keep it up to its trailing space
+        let leading: String = self
+            .components()
+            .take_while(|c| c.range().is_none())
+            .map(|c| c.text())
+            .collect();
+        let prefix = leading.rfind(' ').map_or("", |idx| &leading[..=idx]);
+        // keep everything after the last elided identifier, including its separator
+        let keep_from = idents[idents.len() - max_components - 1] + 1;
+
+        let mut truncated = String::from(prefix);
+        truncated.push('…');
+        for c in self.components().skip(keep_from) {
+            truncated.push_str(c.text());
+        }
+        truncated
+    }
+}
+
+impl From<&ScopeName> for String {
+    fn from(name: &ScopeName) -> Self {
+        name.as_components_str().into_owned()
+    }
 }
 
 impl Display for ScopeName {
@@ -45,7 +234,7 @@ impl NameComponent {
     /// The source text of this component.
     pub fn text(&self) -> &str {
         match &self.inner {
-            NameComponentInner::Interpolation(s) => s,
+            NameComponentInner::Interpolation(s) | NameComponentInner::Key(s) => s,
             NameComponentInner::SourceIdentifierToken(t) => &t.sym,
         }
     }
@@ -71,10 +260,182 @@ impl NameComponent {
             inner: NameComponentInner::SourceIdentifierToken(ident),
         }
     }
+    pub(crate) fn key(s: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            inner: NameComponentInner::Key(s.into()),
+        }
+    }
+
+    /// Whether this is a bracketed property key like `["do-thing"]`, which is
+    /// joined to the preceding name without a `.` separator.
+    pub(crate) fn is_key(&self) -> bool {
+        matches!(self.inner, NameComponentInner::Key(_))
+    }
 }
 
 #[derive(Debug)]
 pub(crate) enum NameComponentInner {
     Interpolation(Cow<'static, str>),
     SourceIdentifierToken(ast::Ident),
+    /// A bracketed property key without a source identifier, like `[0]`.
+    Key(Cow<'static, str>),
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_common::DUMMY_SP;
+
+    use super::*;
+
+    fn name_from(components: impl IntoIterator<Item = NameComponent>) -> ScopeName {
+        ScopeName {
+            components: components.into_iter().collect(),
+        }
+    }
+
+    fn ident(sym: &str) -> NameComponent {
+        NameComponent::ident(ast::Ident::new(sym.into(), DUMMY_SP))
+    }
+
+    #[test]
+    fn leaf() {
+        let name = name_from([ident("foo")]);
+        assert_eq!(name.leaf(), Some("foo"));
+
+        let name = name_from([
+            NameComponent::interp("get "),
+            ident("a"),
+            NameComponent::interp("."),
+            ident("b"),
+            NameComponent::interp(".#"),
+            ident("method"),
+        ]);
+        assert_eq!(name.leaf(), Some("method"));
+
+        let name = name_from([NameComponent::interp("default")]);
+        assert_eq!(name.leaf(), None);
+    }
+
+    #[test]
+    fn semantic_eq() {
+        let name = name_from([
+            NameComponent::interp("new "),
+            ident("Foo"),
+            NameComponent::interp("."),
+            ident("bar"),
+        ]);
+
+        let split = name_from([
+            NameComponent::interp("new"),
+            NameComponent::interp(" "),
+            ident("Foo"),
+            NameComponent::interp("."),
+            NameComponent::interp(""),
+            ident("bar"),
+        ]);
+        assert!(name.semantic_eq(&split));
+
+        let mut normalized = name_from([
+            NameComponent::interp("."),
+            NameComponent::interp("new "),
+            ident("Foo"),
+            NameComponent::interp("."),
+            NameComponent::interp("."),
+            ident("bar"),
+        ]);
+        assert!(name.semantic_eq(&normalized));
+        normalized.normalize();
+        assert!(name.semantic_eq(&normalized));
+        assert!(normalized.semantic_eq(&name));
+
+        let getter = name_from([
+            NameComponent::interp("get "),
+            ident("Foo"),
+            NameComponent::interp("."),
+            ident("bar"),
+        ]);
+        assert!(!name.semantic_eq(&getter));
+
+        let other = name_from([
+            NameComponent::interp("new "),
+            ident("Foo"),
+            NameComponent::interp("."),
+            ident("baz"),
+        ]);
+        assert!(!name.semantic_eq(&other));
+    }
+
+    #[test]
+    fn semantic_eq_collisions() {
+        // a marker is not the same as a separator followed by an identifier
+        let callback = name_from([ident("map"), NameComponent::interp(" callback")]);
+        let member = name_from([ident("map"), NameComponent::interp("."), ident("callback")]);
+        assert!(!callback.semantic_eq(&member));
+
+        // punctuation inside of markers is significant
+        let object = name_from([NameComponent::interp("<object>."), ident("foo")]);
+        let ident_object = name_from([ident("object"), NameComponent::interp("."), ident("foo")]);
+        assert!(!object.semantic_eq(&ident_object));
+
+        // synthetic names are not the same as identifiers with the same text
+        let synthetic = name_from([NameComponent::interp("default")]);
+        let real = name_from([ident("default")]);
+        assert!(!synthetic.semantic_eq(&real));
+
+        // `#` and `.` separators are significant
+        let public = name_from([ident("Foo"), NameComponent::interp("."), ident("bar")]);
+        let private = name_from([ident("Foo"), NameComponent::interp("#"), ident("bar")]);
+        assert!(!public.semantic_eq(&private));
+        let private_sep = name_from([ident("Foo"), NameComponent::interp(".#"), ident("bar")]);
+        assert!(!public.semantic_eq(&private_sep));
+        assert!(!private.semantic_eq(&private_sep));
+    }
+
+    #[test]
+    fn normalize_separators() {
+        let mut name = name_from([
+            NameComponent::interp("."),
+            ident("Foo"),
+            NameComponent::interp("."),
+            NameComponent::interp("."),
+            ident("bar"),
+        ]);
+        assert_eq!(name.to_string(), ".Foo..bar");
+
+        name.normalize();
+        assert_eq!(name.to_string(), "Foo.bar");
+        assert_eq!(name.components().count(), 3);
+    }
+
+    #[test]
+    fn normalize_merges_interpolations() {
+        let mut name = name_from([
+            NameComponent::interp("get "),
+            NameComponent::interp("<object>."),
+            NameComponent::interp("."),
+            ident("a"),
+            NameComponent::interp("."),
+            NameComponent::interp("#"),
+            ident("b"),
+        ]);
+
+        name.normalize();
+        let components: Vec<_> = name.components().map(|c| c.text()).collect();
+        assert_eq!(components, ["get <object>.", "a", ".#", "b"]);
+
+        // the leading marker is still preserved when truncating
+        assert_eq!(name.to_truncated_string(1), "get ….#b");
+    }
+
+    #[test]
+    fn normalize_only_separators() {
+        let mut name = name_from([NameComponent::interp("."), NameComponent::interp(".")]);
+
+        name.normalize();
+        assert_eq!(name.components().count(), 0);
+
+        let mut name = name_from([ident("a"), NameComponent::interp("[\"..\"]")]);
+        name.normalize();
+        assert_eq!(name.to_string(), "a[\"..\"]");
+    }
 }
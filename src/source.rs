@@ -1,3 +1,7 @@
+use std::ops::Range;
+
+use crate::NameComponent;
+
 /// A structure allowing fast line/column <-> byte offset remapping.
 ///
 /// The primary use-case is to allow efficient conversion between
@@ -6,6 +10,12 @@
 /// [`SourceContext`] for a source that exceeds the range of a `u32` will result
 /// in an `Err`.
 ///
+/// A leading UTF-8 byte order mark (`U+FEFF`) is not counted towards the
+/// columns of the first line, as it is not part of the source text that
+/// browsers and sourcemaps see. Byte offsets are still relative to the start of
+/// the buffer including the BOM, which matches the ranges returned by
+/// [`extract_scope_names`](crate::extract_scope_names).
+///
 /// # Examples
 ///
 /// ```
@@ -59,10 +69,10 @@ impl<T: AsRef<str>> SourceContext<T> {
 
         let mut index = vec![];
 
-        let mut offset = 0;
+        let mut offset = bom_len(buf);
         let mut line = 0;
         let mut column = 0;
-        for (i, c) in buf.chars().enumerate() {
+        for (i, c) in buf[offset..].chars().enumerate() {
             if i % CHUNKS == 0 {
                 index.push(Mapping {
                     offset: offset as u32,
@@ -82,20 +92,49 @@ impl<T: AsRef<str>> SourceContext<T> {
         Ok(Self { src, index })
     }
 
+    /// The [`Mapping`] at the start of the source, after a leading BOM.
+    fn start_mapping(&self) -> Mapping {
+        Mapping {
+            offset: bom_len(self.src.as_ref()) as u32,
+            line: 0,
+            column: 0,
+        }
+    }
+
     /// Converts a byte offset into the source to the corresponding line/column.
     ///
     /// The column is given in UTF-16 code points.
     pub fn offset_to_position(&self, offset: u32) -> Option<SourcePosition> {
+        self.position_at(offset, false)
+    }
+
+    /// Converts a byte `range` inside the source to its start and end line/column.
+    ///
+    /// Contrary to [`offset_to_position`](Self::offset_to_position), this also
+    /// accepts offsets at the very end of the source, as a range can end there.
+    ///
+    /// This can be used to convert the byte ranges of the [`Scopes`](crate::Scopes)
+    /// returned by [`extract_scope_names`](crate::extract_scope_names) to
+    /// line/column spans.
+    pub fn range_to_positions(
+        &self,
+        range: Range<u32>,
+    ) -> Option<(SourcePosition, SourcePosition)> {
+        let start = self.position_at(range.start, true)?;
+        let end = self.position_at(range.end, true)?;
+
+        Some((start, end))
+    }
+
+    /// Converts a byte offset into the corresponding line/column, optionally
+    /// accepting the `offset` at the end of the source.
+    fn position_at(&self, offset: u32, allow_eof: bool) -> Option<SourcePosition> {
         let mapping = match self
             .index
             .binary_search_by_key(&offset, |mapping| mapping.offset)
         {
             Ok(idx) => self.index[idx],
-            Err(0) => Mapping {
-                offset: 0,
-                line: 0,
-                column: 0,
-            },
+            Err(0) => self.start_mapping(),
             Err(idx) => self.index[idx - 1],
         };
 
@@ -117,9 +156,25 @@ impl<T: AsRef<str>> SourceContext<T> {
             }
         }
 
+        if allow_eof && byte_offset == offset as usize {
+            return Some(SourcePosition::new(line, column as u32));
+        }
+
         None
     }
 
+    /// Converts the byte range of the given [`NameComponent`] to its start and
+    /// end line/column.
+    ///
+    /// Returns `None` for synthetic components that do not correspond to a
+    /// token inside the source text.
+    pub fn component_span(
+        &self,
+        component: &NameComponent,
+    ) -> Option<(SourcePosition, SourcePosition)> {
+        self.range_to_positions(component.range()?)
+    }
+
     /// Converts the given line/column to the corresponding byte offset inside the source.
     pub fn position_to_offset(&self, position: SourcePosition) -> Option<u32> {
         let SourcePosition { line, column } = position;
@@ -128,11 +183,7 @@ impl<T: AsRef<str>> SourceContext<T> {
             .binary_search_by_key(&(line, column), |mapping| (mapping.line, mapping.column))
         {
             Ok(idx) => self.index[idx],
-            Err(0) => Mapping {
-                offset: 0,
-                line: 0,
-                column: 0,
-            },
+            Err(0) => self.start_mapping(),
             Err(idx) => self.index[idx - 1],
         };
 
@@ -162,7 +213,24 @@ impl<T: AsRef<str>> SourceContext<T> {
     }
 }
 
+/// The length in bytes of a leading UTF-8 byte order mark, if `src` has one.
+fn bom_len(src: &str) -> usize {
+    if src.starts_with('\u{feff}') {
+        '\u{feff}'.len_utf8()
+    } else {
+        0
+    }
+}
+
 /// A line/column source position.
+///
+/// Both the line and column are 0-based, which matches the raw `dst_line` and
+/// `dst_col` values of [`sourcemap`](https://docs.rs/sourcemap) tokens.
+/// Browsers and stack traces usually report 1-based values, which need to be
+/// adjusted accordingly.
+///
+/// Positions are ordered by line first, and column second. That makes them
+/// suitable for binary searching through a list of positions in source order.
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
 pub struct SourcePosition {
     /// Line in the source file, 0-based.
@@ -174,10 +242,37 @@ pub struct SourcePosition {
 }
 
 impl SourcePosition {
-    /// Create a new SourcePosition with the given line/column.
+    /// Create a new SourcePosition with the given 0-based line/column.
+    ///
+    /// The first character of the source is at line `0`, column `0`. This is
+    /// the convention used by sourcemap tokens, so their `dst_line` and
+    /// `dst_col` can be passed directly. Use
+    /// [`from_one_based`](Self::from_one_based) for 1-based positions, as
+    /// reported by browsers and stack traces.
     pub fn new(line: u32, column: u32) -> Self {
         Self { line, column }
     }
+
+    /// Create a new SourcePosition from the given 1-based line/column.
+    ///
+    /// The first character of the source is at line `1`, column `1`.
+    /// Returns `None` if either the `line` or `column` is `0`, as that is not a
+    /// valid 1-based position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use js_source_scopes::SourcePosition;
+    ///
+    /// assert_eq!(SourcePosition::from_one_based(1, 7), Some(SourcePosition::new(0, 6)));
+    /// assert_eq!(SourcePosition::from_one_based(0, 7), None);
+    /// ```
+    pub fn from_one_based(line: u32, column: u32) -> Option<Self> {
+        Some(Self {
+            line: line.checked_sub(1)?,
+            column: column.checked_sub(1)?,
+        })
+    }
 }
 
 /// An Error that can happen when building a [`SourceContext`].
@@ -227,4 +322,95 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn utf16_columns() {
+        // The emoji is outside the BMP, so it is 4 bytes in UTF-8, and a surrogate
+        // pair of 2 code units in UTF-16, which is what sourcemap columns are based on.
+        let src = "const a = \"🤮\"; function ident() {}\n";
+        let ctx = SourceContext::new(src).unwrap();
+
+        let offset = src.find("ident").unwrap() as u32;
+        assert_eq!(offset, 27);
+
+        let position = ctx.offset_to_position(offset).unwrap();
+        assert_eq!(position, SourcePosition::new(0, 25));
+        assert_eq!(ctx.position_to_offset(position), Some(offset));
+    }
+
+    #[test]
+    fn leading_bom() {
+        let src = "const a = 1;\nfunction ident() {}\n";
+        let bom_src = format!("\u{feff}{src}");
+        let ctx = SourceContext::new(src).unwrap();
+        let bom_ctx = SourceContext::new(bom_src.as_str()).unwrap();
+
+        for (offset, _c) in src.char_indices() {
+            let position = ctx.offset_to_position(offset as u32).unwrap();
+            let bom_offset = offset as u32 + 3;
+            assert_eq!(bom_ctx.offset_to_position(bom_offset), Some(position));
+            assert_eq!(bom_ctx.position_to_offset(position), Some(bom_offset));
+        }
+
+        // the BOM itself maps to the start of the source
+        assert_eq!(
+            bom_ctx.offset_to_position(0),
+            Some(SourcePosition::new(0, 0))
+        );
+        assert_eq!(
+            bom_ctx.position_to_offset(SourcePosition::new(0, 0)),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn source_position_constructors() {
+        assert_eq!(
+            SourcePosition::new(0, 0),
+            SourcePosition { line: 0, column: 0 }
+        );
+        assert_eq!(
+            SourcePosition::from_one_based(1, 1),
+            Some(SourcePosition::new(0, 0))
+        );
+        assert_eq!(
+            SourcePosition::from_one_based(3, 10),
+            Some(SourcePosition::new(2, 9))
+        );
+
+        // there is no line or column `0` in 1-based positions
+        assert_eq!(SourcePosition::from_one_based(0, 1), None);
+        assert_eq!(SourcePosition::from_one_based(1, 0), None);
+        assert_eq!(SourcePosition::from_one_based(0, 0), None);
+
+        let src = "const a = 1;\nfunction ident() {}\n";
+        let ctx = SourceContext::new(src).unwrap();
+        let expected = src.find("ident").map(|offset| offset as u32);
+
+        let zero_based = SourcePosition::new(1, 9);
+        assert_eq!(ctx.position_to_offset(zero_based), expected);
+        let one_based = SourcePosition::from_one_based(2, 10).unwrap();
+        assert_eq!(ctx.position_to_offset(one_based), expected);
+    }
+
+    #[test]
+    fn source_position_ordering() {
+        let positions = [
+            SourcePosition::new(0, 0),
+            SourcePosition::new(0, 1),
+            SourcePosition::new(0, u32::MAX),
+            SourcePosition::new(1, 0),
+            SourcePosition::new(1, 5),
+            SourcePosition::new(2, 0),
+        ];
+
+        for pair in positions.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+
+        // a later line always sorts after an earlier line, regardless of column
+        assert!(SourcePosition::new(1, 0) > SourcePosition::new(0, 100));
+        assert_eq!(positions.binary_search(&SourcePosition::new(1, 5)), Ok(4));
+        assert_eq!(positions.binary_search(&SourcePosition::new(1, 3)), Err(4));
+    }
 }
@@ -1,32 +1,132 @@
 use std::ops::Range;
 
+use swc_common::comments::{Comment, Comments, SingleThreadedComments};
 use swc_common::{BytePos, Span};
+use swc_ecma_parser::lexer::Lexer;
 use swc_ecma_parser::{Parser, StringInput};
+use swc_ecma_visit::fields::{ArrowExprField, CallExprField, OptCallField, SeqExprField};
 use swc_ecma_visit::swc_ecma_ast as ast;
 use swc_ecma_visit::{AstNodePath, VisitAstPath, VisitWithPath};
 
 use crate::scope_name::{NameComponent, ScopeName};
-use crate::Scopes;
+use crate::{ParseOptions, ScopeInfo, ScopeKind};
 
 pub(crate) use swc_ecma_parser::error::Error as ParseError;
 
-pub fn parse_with_swc(src: &str) -> Result<Scopes, ParseError> {
-    let syntax = tracing::trace_span!("parsing source").in_scope(|| {
-        let input = StringInput::new(src, BytePos(0), BytePos(src.len() as u32));
+/// A callback receiving each scope, its inferred name and info as soon as it is found.
+pub(crate) type OnScope<'a> = dyn FnMut(Range<u32>, ScopeName, ScopeInfo) + 'a;
 
-        let mut parser = Parser::new(swc_ecma_parser::Syntax::default(), input, None);
-
-        parser.parse_module()
-    })?;
+/// Parses the `src`, which is located at `base_offset` inside a larger file,
+/// and visits all of its function scopes.
+pub fn parse_with_swc(
+    src: &str,
+    base_offset: u32,
+    options: &ParseOptions,
+    on_scope: &mut OnScope,
+) -> Result<(), ParseError> {
+    let (syntax, comments) = tracing::trace_span!("parsing source")
+        .in_scope(|| parse_program(src, base_offset, options))?;
 
     // dbg!(&syntax);
 
+    visit_scopes(&syntax, options, Some(&comments), on_scope);
+
+    Ok(())
+}
+
+/// Visits the function scopes within an already parsed `node`, passing each of
+/// them to `on_scope` in the order they are found.
+///
+/// Leading pragma comments of the scopes are only detected if the `comments`
+/// collected while parsing the `node` are given.
+pub(crate) fn visit_scopes<N>(
+    node: &N,
+    options: &ParseOptions,
+    comments: Option<&dyn Comments>,
+    on_scope: &mut OnScope,
+) where
+    N: for<'a> VisitWithPath<ScopeCollector<'a>>,
+{
     tracing::trace_span!("extracting scopes").in_scope(|| {
-        let mut collector = ScopeCollector::new();
+        let mut collector = ScopeCollector::new(options, comments, on_scope);
 
-        syntax.visit_children_with_path(&mut collector, &mut Default::default());
+        node.visit_children_with_path(&mut collector, &mut Default::default());
+    })
+}
+
+/// Parses the `src` and collects the `(local, exported)` names of all the
+/// `export { local as exported }` specifiers that rename their binding.
+pub(crate) fn parse_export_aliases(
+    src: &str,
+    options: &ParseOptions,
+) -> Result<Vec<(String, String)>, ParseError> {
+    let module = match parse_program(src, 0, options)?.0 {
+        ast::Program::Module(module) => module,
+        // scripts can not have any exports
+        ast::Program::Script(_) => return Ok(vec![]),
+    };
+
+    let aliases = module
+        .body
+        .iter()
+        .filter_map(|item| item.as_module_decl()?.as_export_named())
+        // re-exports from other modules do not refer to local scopes
+        .filter(|export| export.src.is_none())
+        .flat_map(|export| &export.specifiers)
+        .filter_map(|specifier| {
+            let specifier = specifier.as_named()?;
+            let local = module_export_name(&specifier.orig);
+            let exported = module_export_name(specifier.exported.as_ref()?);
+            (local != exported).then(|| (local.to_owned(), exported.to_owned()))
+        })
+        .collect();
+
+    Ok(aliases)
+}
 
-        Ok(collector.into_scopes())
+/// The name of an export specifier, which can also be a string literal:
+/// `export { foo as "foo bar" }`
+fn module_export_name(name: &ast::ModuleExportName) -> &str {
+    match name {
+        ast::ModuleExportName::Ident(ident) => &ident.sym,
+        ast::ModuleExportName::Str(lit) => &lit.value,
+    }
+}
+
+/// Parses the `src` as a [`ast::Module`], falling back to an [`ast::Script`].
+///
+/// Classic (non-module) scripts can contain constructs that are illegal in
+/// strict module code. Most of those, like a top-level `return` or `with`
+/// statements, are only reported as recoverable errors, but some, like using
+/// `await` as an identifier, make parsing as a module fail entirely.
+/// If parsing as a script fails as well, the error of the module parse is returned.
+///
+/// The comments of the source are returned alongside the parsed program. Each
+/// attempt collects them into a fresh store, so the comments seen by a failed
+/// module parse are not recorded twice.
+fn parse_program(
+    src: &str,
+    base_offset: u32,
+    options: &ParseOptions,
+) -> Result<(ast::Program, SingleThreadedComments), ParseError> {
+    let parse = |as_module: bool| {
+        let comments = SingleThreadedComments::default();
+        let end = base_offset + src.len() as u32;
+        let input = StringInput::new(src, BytePos(base_offset), BytePos(end));
+        let lexer = Lexer::new(options.syntax, options.target, input, Some(&comments));
+        let mut parser = Parser::new_from(lexer);
+
+        let program = if as_module {
+            parser.parse_module().map(ast::Program::Module)
+        } else {
+            parser.parse_script().map(ast::Program::Script)
+        };
+        program.map(|program| (program, comments))
+    };
+
+    parse(true).or_else(|err| {
+        tracing::trace!("parsing as module failed, retrying as script");
+        parse(false).map_err(|_| err)
     })
 }
 
@@ -48,31 +148,62 @@ pub(crate) fn convert_span(span: Span) -> Range<u32> {
 /// 2. We use the "path" to walk up to the VariableDeclarator.
 /// 3. That declarator has a binding pattern on the left hand side, which we use
 ///    to infer the `name` for the anonymous arrow function expression.
-struct ScopeCollector {
-    scopes: Scopes,
+pub(crate) struct ScopeCollector<'a> {
+    on_scope: &'a mut OnScope<'a>,
+    options: &'a ParseOptions,
+    comments: Option<&'a dyn Comments>,
 }
 
-impl ScopeCollector {
-    fn new() -> Self {
-        Self { scopes: vec![] }
+impl<'a> ScopeCollector<'a> {
+    fn new(
+        options: &'a ParseOptions,
+        comments: Option<&'a dyn Comments>,
+        on_scope: &'a mut OnScope<'a>,
+    ) -> Self {
+        Self {
+            on_scope,
+            options,
+            comments,
+        }
     }
 
-    fn into_scopes(self) -> Scopes {
-        self.scopes
+    /// Passes the scope spanning `span` to the `on_scope` callback.
+    fn emit(&mut self, span: Span, name: ScopeName, kind: ScopeKind) {
+        let has_leading_pragma = self
+            .comments
+            .and_then(|comments| comments.get_leading(span.lo))
+            .is_some_and(|comments| comments.iter().any(is_pragma));
+        let info = ScopeInfo {
+            kind,
+            has_leading_pragma,
+        };
+
+        (self.on_scope)(convert_span(span), name, info);
     }
 }
 
+/// Whether the `comment` is a bundler pragma like `/*#__PURE__*/`.
+fn is_pragma(comment: &Comment) -> bool {
+    let text = comment.text.trim();
+    let pragma = text
+        .strip_prefix('#')
+        .or_else(|| text.strip_prefix('@'))
+        .unwrap_or_default();
+
+    matches!(pragma, "__PURE__" | "__NO_SIDE_EFFECTS__")
+}
+
 use swc_ecma_visit::AstParentNodeRef as Parent;
 
-impl VisitAstPath for ScopeCollector {
+impl VisitAstPath for ScopeCollector<'_> {
     fn visit_arrow_expr<'ast: 'r, 'r>(
         &mut self,
         node: &'ast ast::ArrowExpr,
         path: &mut AstNodePath<'r>,
     ) {
-        let name = infer_name_from_ctx(path);
+        let name = infer_name_from_ctx(path, self.options);
 
-        self.scopes.push((convert_span(node.span), Some(name)));
+        self.emit(node.span, name, ScopeKind::Arrow);
 
         node.visit_children_with_path(self, path);
     }
@@ -87,9 +218,15 @@ impl VisitAstPath for ScopeCollector {
             Some(Parent::FnExpr(fn_expr, _)) => fn_expr.ident.clone(),
             _ => None,
         };
-        let name = name_from_ident_or_ctx(ident, path);
+        let name = name_from_ident_or_ctx(ident, path, self.options);
+        let kind = match path.last() {
+            Some(Parent::ClassMethod(method, _)) => method_scope_kind(method.kind),
+            Some(Parent::PrivateMethod(method, _)) => method_scope_kind(method.kind),
+            Some(Parent::MethodProp(..)) => ScopeKind::Method,
+            _ => ScopeKind::Function,
+        };
 
-        self.scopes.push((convert_span(node.span), Some(name)));
+        self.emit(node.span, name, kind);
 
         node.visit_children_with_path(self, path);
     }
@@ -103,12 +240,29 @@ impl VisitAstPath for ScopeCollector {
             Some(Parent::ClassExpr(class_expr, _)) => class_expr.ident.clone(),
             _ => None,
         };
-        let mut name = name_from_ident_or_ctx(ident, path);
+        let mut name = name_from_ident_or_ctx(ident, path, self.options);
+        if !name.components.is_empty() && !self.options.class_prefix.is_empty() {
+            name.components
+                .push_front(NameComponent::interp(self.options.class_prefix));
+        }
+
+        self.emit(node.span, name, ScopeKind::Class);
+
+        node.visit_children_with_path(self, path);
+    }
+
+    fn visit_static_block<'ast: 'r, 'r>(
+        &mut self,
+        node: &'ast ast::StaticBlock,
+        path: &mut AstNodePath<'r>,
+    ) {
+        let mut name = infer_name_from_ctx(path, self.options);
         if !name.components.is_empty() {
-            name.components.push_front(NameComponent::interp("new "));
+            name.components.push_back(NameComponent::interp("."));
+            name.components.push_back(NameComponent::interp("<static>"));
         }
 
-        self.scopes.push((convert_span(node.span), Some(name)));
+        self.emit(node.span, name, ScopeKind::StaticBlock);
 
         node.visit_children_with_path(self, path);
     }
@@ -118,16 +272,16 @@ impl VisitAstPath for ScopeCollector {
         node: &'ast ast::GetterProp,
         path: &mut AstNodePath<'r>,
     ) {
-        let mut name = infer_name_from_ctx(path);
+        let mut name = infer_name_from_ctx(path, self.options);
 
-        if !name.components.is_empty() {
+        let key = prop_name_to_component(&node.key);
+        if !name.components.is_empty() && !key.is_key() {
             name.components.push_back(NameComponent::interp("."));
         }
-
-        name.components.push_back(prop_name_to_component(&node.key));
+        name.components.push_back(key);
         name.components.push_front(NameComponent::interp("get "));
 
-        self.scopes.push((convert_span(node.span), Some(name)));
+        self.emit(node.span, name, ScopeKind::Getter);
 
         node.visit_children_with_path(self, path);
     }
@@ -137,24 +291,37 @@ impl VisitAstPath for ScopeCollector {
         node: &'ast ast::SetterProp,
         path: &mut AstNodePath<'r>,
     ) {
-        let mut name = infer_name_from_ctx(path);
+        let mut name = infer_name_from_ctx(path, self.options);
 
-        if !name.components.is_empty() {
+        let key = prop_name_to_component(&node.key);
+        if !name.components.is_empty() && !key.is_key() {
             name.components.push_back(NameComponent::interp("."));
         }
-
-        name.components.push_back(prop_name_to_component(&node.key));
+        name.components.push_back(key);
         name.components.push_front(NameComponent::interp("set "));
 
-        self.scopes.push((convert_span(node.span), Some(name)));
+        self.emit(node.span, name, ScopeKind::Setter);
 
         node.visit_children_with_path(self, path);
     }
 }
 
+/// Maps the [`ast::MethodKind`] of a class method to its [`ScopeKind`].
+fn method_scope_kind(kind: ast::MethodKind) -> ScopeKind {
+    match kind {
+        ast::MethodKind::Method => ScopeKind::Method,
+        ast::MethodKind::Getter => ScopeKind::Getter,
+        ast::MethodKind::Setter => ScopeKind::Setter,
+    }
+}
+
 /// Uses either the provided [`ast::Ident`] or infers the name from the `path`.
-fn name_from_ident_or_ctx(ident: Option<ast::Ident>, path: &AstNodePath) -> ScopeName {
-    let mut name = infer_name_from_ctx(path);
+fn name_from_ident_or_ctx(
+    ident: Option<ast::Ident>,
+    path: &[Parent],
+    options: &ParseOptions,
+) -> ScopeName {
+    let mut name = infer_name_from_ctx(path, options);
     if let Some(ident) = ident {
         name.components.pop_back();
         name.components.push_back(NameComponent::ident(ident));
@@ -164,26 +331,52 @@ fn name_from_ident_or_ctx(ident: Option<ast::Ident>, path: &AstNodePath) -> Scop
 }
 
 /// Tries to infer a name by walking up the path of ancestors.
-fn infer_name_from_ctx(path: &AstNodePath) -> ScopeName {
+fn infer_name_from_ctx(path: &[Parent], options: &ParseOptions) -> ScopeName {
     let mut scope_name = ScopeName::new();
     let mut kind = ast::MethodKind::Method;
     let mut in_object_lit = false;
 
     fn push_sep(name: &mut ScopeName) {
-        if !name.components.is_empty() {
+        if name.components.front().is_some_and(|c| !c.is_key()) {
             name.components.push_front(NameComponent::interp("."));
         }
     }
 
-    for parent in path.iter().rev() {
+    for (idx, parent) in path.iter().enumerate().rev() {
         match parent {
+            // A function directly returned from a named factory function, or
+            // from the concise body of an arrow function, like a curried one:
+            // `function $name() { return function () {}; }`
+            // `const $name = (a) => (b) => {}`
+            // Curried arrow functions are always named, returned functions only
+            // if enabled.
+            Parent::ReturnStmt(..) | Parent::ArrowExpr(_, ArrowExprField::Body)
+                if (options.infer_returned_function_names
+                    || matches!(parent, Parent::ArrowExpr(..)))
+                    && scope_name.components.is_empty()
+                    && is_directly_returned(&path[idx + 1..]) =>
+            {
+                if let Some(factory_name) = infer_enclosing_fn_name(&path[..=idx], options) {
+                    scope_name = factory_name;
+                    scope_name.components.push_back(NameComponent::interp("."));
+                    scope_name
+                        .components
+                        .push_back(NameComponent::interp("<return>"));
+                }
+
+                return scope_name;
+            }
+
             // These create a new scope. If we reached this, it means we didn’t
             // use any of the other parents properly.
-            Parent::Function(..) | Parent::ArrowExpr(..) | Parent::Constructor(..) => {
+            Parent::Function(..)
+            | Parent::ArrowExpr(..)
+            | Parent::Constructor(..)
+            | Parent::GetterProp(..)
+            | Parent::SetterProp(..)
+            | Parent::StaticBlock(..) => {
                 if in_object_lit && !scope_name.components.is_empty() {
-                    scope_name
-                        .components
-                        .push_front(NameComponent::interp("<object>."));
+                    push_object_prefix(&mut scope_name);
                 }
                 prefix_getters_setters(kind, &mut scope_name);
                 return scope_name;
@@ -221,13 +414,11 @@ fn infer_name_from_ctx(path: &AstNodePath) -> ScopeName {
 
             // An object literal property:
             // `{ $name: ... }`
-            Parent::KeyValueProp(kv, _) => {
-                if let Some(ident) = kv.key.as_ident() {
-                    push_sep(&mut scope_name);
-                    scope_name
-                        .components
-                        .push_front(NameComponent::ident(ident.clone()));
-                }
+            Parent::KeyValueProp(kv, _) if !matches!(kv.key, ast::PropName::Computed(_)) => {
+                push_sep(&mut scope_name);
+                scope_name
+                    .components
+                    .push_front(prop_name_to_component(&kv.key));
             }
 
             // A class method:
@@ -247,6 +438,27 @@ fn infer_name_from_ctx(path: &AstNodePath) -> ScopeName {
                     .components
                     .push_front(NameComponent::ident(method.key.id.clone()));
                 scope_name.components.push_front(NameComponent::interp("#"));
+
+                kind = method.kind;
+            }
+
+            // A class field, which is initialized as part of the constructor:
+            // `class { $name = ... }`
+            Parent::ClassProp(prop, _) => {
+                push_sep(&mut scope_name);
+                scope_name
+                    .components
+                    .push_front(prop_name_to_component(&prop.key));
+            }
+
+            // A private class field:
+            // `class { #$name = ... }`
+            Parent::PrivateProp(prop, _) => {
+                push_sep(&mut scope_name);
+                scope_name
+                    .components
+                    .push_front(NameComponent::ident(prop.key.id.clone()));
+                scope_name.components.push_front(NameComponent::interp("#"));
             }
 
             // A variable declaration with a name:
@@ -264,6 +476,56 @@ fn infer_name_from_ctx(path: &AstNodePath) -> ScopeName {
                 }
             }
 
+            // A default value in an object destructuring pattern:
+            // `const { $name = ... } = obj`
+            Parent::AssignPatProp(prop, _) => {
+                push_sep(&mut scope_name);
+                scope_name
+                    .components
+                    .push_front(NameComponent::ident(prop.key.id.clone()));
+
+                prefix_getters_setters(kind, &mut scope_name);
+
+                return scope_name;
+            }
+
+            // A default value in an array destructuring pattern or parameter:
+            // `const [$name = ...] = arr`
+            // `function ($name = ...) {}`
+            Parent::AssignPat(pat, _) => {
+                if let Some(ident) = pat.left.as_ident() {
+                    push_sep(&mut scope_name);
+                    scope_name
+                        .components
+                        .push_front(NameComponent::ident(ident.id.clone()));
+
+                    prefix_getters_setters(kind, &mut scope_name);
+
+                    return scope_name;
+                }
+            }
+
+            // A default export:
+            // `export default function () {}`
+            // `export default class {}`
+            Parent::ExportDefaultDecl(export, _) => {
+                let has_ident = match &export.decl {
+                    ast::DefaultDecl::Class(class_expr) => class_expr.ident.is_some(),
+                    ast::DefaultDecl::Fn(fn_expr) => fn_expr.ident.is_some(),
+                    _ => true,
+                };
+                if !has_ident {
+                    push_sep(&mut scope_name);
+                    scope_name
+                        .components
+                        .push_front(NameComponent::interp("default"));
+                }
+
+                prefix_getters_setters(kind, &mut scope_name);
+
+                return scope_name;
+            }
+
             // An assignment expression with a usable name on the left hand side
             // `$name = ...`
             Parent::AssignExpr(expr, _) => match &expr.left {
@@ -278,16 +540,40 @@ fn infer_name_from_ctx(path: &AstNodePath) -> ScopeName {
                     return scope_name;
                 }
                 ast::AssignTarget::Simple(ast::SimpleAssignTarget::Member(member)) => {
-                    if let Some(mut expr_name) = infer_name_from_expr(&member.obj) {
+                    // A CommonJS default export:
+                    // `module.exports = ...`
+                    if options.strip_commonjs_exports && is_module_exports(member) {
+                        push_sep(&mut scope_name);
+                        scope_name
+                            .components
+                            .push_front(NameComponent::interp("default"));
+
+                        prefix_getters_setters(kind, &mut scope_name);
+
+                        return scope_name;
+                    }
+
+                    if let Some(mut expr_name) =
+                        infer_name_from_expr(&member.obj, &path[..idx], options)
+                    {
                         if let Some(ident) = member.prop.as_ident() {
                             scope_name
                                 .components
                                 .push_front(NameComponent::ident(ident.clone()));
                             push_sep(&mut scope_name);
+
+                            // The object was a stripped `exports`, so we do not
+                            // want a leading separator.
+                            if expr_name.components.is_empty() {
+                                scope_name.components.pop_front();
+                            }
                         }
 
+                        // A dynamic key makes the assignment anonymous.
                         if let Some(computed_prop) = member.prop.as_computed() {
-                            push_computed_prop_name(computed_prop, &mut scope_name)
+                            if push_computed_prop_name(computed_prop, &mut scope_name).is_none() {
+                                return ScopeName::new();
+                            }
                         }
 
                         expr_name.components.append(&mut scope_name.components);
@@ -305,17 +591,421 @@ fn infer_name_from_ctx(path: &AstNodePath) -> ScopeName {
                 in_object_lit = true;
             }
 
+            // A sequence expression, as emitted by minifiers:
+            // `const $name = (a(), function () {})`
+            // Only the last expression is the resulting value, so only that one
+            // is named after whatever the sequence is assigned to.
+            Parent::SeqExpr(seq, SeqExprField::Exprs(expr_idx))
+                if *expr_idx + 1 != seq.exprs.len() =>
+            {
+                break;
+            }
+
+            // An accessor in a property descriptor, as emitted by transpilers:
+            // `Object.defineProperty($obj, "$name", { get: function () {} })`
+            Parent::CallExpr(call, CallExprField::Args(2))
+                if options.infer_callback_names && !scope_name.components.is_empty() =>
+            {
+                if let Some(name) =
+                    infer_define_property_name(call, &path[..idx], &path[idx + 1..], options)
+                {
+                    return name;
+                }
+            }
+
+            // A function passed as an argument to a call:
+            // `callee(function () {})`
+            // Only the callee of an IIFE is named after whatever the call result
+            // is assigned to, arguments stay anonymous.
+            Parent::CallExpr(call, CallExprField::Args(arg_idx))
+                if scope_name.components.is_empty() =>
+            {
+                if is_spread_arg(&call.args, *arg_idx) {
+                    return scope_name;
+                }
+
+                // A function passed to a class decorator:
+                // `@$decorator(() => {}) class $name {}`
+                if options.infer_callback_names {
+                    if let Some(decorator_name) = infer_decorator_arg_name(call, &path[..idx]) {
+                        return decorator_name;
+                    }
+                }
+
+                if let Some(callee) = call.callee.as_expr() {
+                    return infer_call_arg_name(
+                        callee,
+                        &call.args,
+                        *arg_idx,
+                        &path[..idx],
+                        options,
+                    );
+                }
+
+                return scope_name;
+            }
+
+            // A function passed as an argument to an optional call:
+            // `callee?.(function () {})`
+            // `obj?.method(function () {})`
+            Parent::OptCall(call, OptCallField::Args(arg_idx))
+                if scope_name.components.is_empty() =>
+            {
+                if is_spread_arg(&call.args, *arg_idx) {
+                    return scope_name;
+                }
+
+                return infer_call_arg_name(
+                    &call.callee,
+                    &call.args,
+                    *arg_idx,
+                    &path[..idx],
+                    options,
+                );
+            }
+
             _ => {}
         }
     }
 
     if in_object_lit && !scope_name.components.is_empty() {
+        push_object_prefix(&mut scope_name);
+    }
+    prefix_getters_setters(kind, &mut scope_name);
+    scope_name
+}
+
+/// Prefixes the `scope_name` of an object literal member with the `<object>` marker.
+fn push_object_prefix(scope_name: &mut ScopeName) {
+    let prefix = match scope_name.components.front() {
+        Some(c) if c.is_key() => "<object>",
+        _ => "<object>.",
+    };
+    scope_name
+        .components
+        .push_front(NameComponent::interp(prefix));
+}
+
+/// Infers a name for a function passed as argument number `arg_idx` to a call
+/// of `callee`, which is at the end of `path`.
+///
+/// Some well-known callbacks are named after the variable the call initializes,
+/// or otherwise after the call itself:
+/// `const $name = useCallback(() => {}, [])`
+/// `useEffect(() => {}, [])`
+/// Any other argument stays anonymous.
+fn infer_call_arg_name(
+    callee: &ast::Expr,
+    args: &[ast::ExprOrSpread],
+    arg_idx: usize,
+    path: &[Parent],
+    options: &ParseOptions,
+) -> ScopeName {
+    let Some(callback_name) = infer_callback_name(callee, args, arg_idx, options) else {
+        return ScopeName::new();
+    };
+
+    match declarator_ident(path) {
+        Some(ident) => {
+            let mut scope_name = ScopeName::new();
+            scope_name.components.push_back(NameComponent::ident(ident));
+            scope_name
+        }
+        None => callback_name,
+    }
+}
+
+/// Infers a name for a callback passed as argument number `arg_idx` to a call
+/// of `callee` with the given `args`.
+///
+/// React hook callbacks are always named, like `useEffect callback`.
+/// Other heuristics are only applied if [`ParseOptions::infer_callback_names`]
+/// is enabled:
+/// - Event listeners are named after their event, like `<click handler>`.
+/// - Callbacks passed to any other method are named after that method, like
+///   `map callback` for `items.map(() => {})`.
+/// - Callbacks passed to timers are named after the timer function, like
+///   `setTimeout callback`.
+///
+/// Functions passed to class decorators are named separately, see
+/// [`infer_decorator_arg_name`].
+fn infer_callback_name(
+    callee: &ast::Expr,
+    args: &[ast::ExprOrSpread],
+    arg_idx: usize,
+    options: &ParseOptions,
+) -> Option<ScopeName> {
+    let mut scope_name = ScopeName::new();
+
+    if arg_idx == 0 {
+        if let Some(hook) = react_hook_ident(callee) {
+            scope_name.components.push_back(NameComponent::ident(hook));
+            scope_name
+                .components
+                .push_back(NameComponent::interp(" callback"));
+            return Some(scope_name);
+        }
+    }
+
+    if !options.infer_callback_names {
+        return None;
+    }
+
+    // Timers and animation frames:
+    // `setTimeout(() => {})`
+    if let ast::Expr::Ident(ident) = callee {
+        let is_scheduler = matches!(
+            &*ident.sym,
+            "setTimeout" | "setInterval" | "requestAnimationFrame"
+        );
+        if arg_idx != 0 || !is_scheduler {
+            return None;
+        }
         scope_name
             .components
-            .push_front(NameComponent::interp("<object>."));
+            .push_back(NameComponent::ident(ident.clone()));
+        scope_name
+            .components
+            .push_back(NameComponent::interp(" callback"));
+        return Some(scope_name);
     }
-    prefix_getters_setters(kind, &mut scope_name);
+
+    let method = member_callee_ident(callee)?;
+    match &*method.sym {
+        "addEventListener" if arg_idx == 1 => {
+            let event = match args.first()?.expr.as_lit()? {
+                ast::Lit::Str(event) => &event.value,
+                _ => return None,
+            };
+            scope_name
+                .components
+                .push_back(NameComponent::interp(format!("<{event} handler>")));
+        }
+        // Array methods, promises, or any fluent API:
+        // `items.map(() => {})`
+        // `foo.bar().$method(() => {})`
+        _ => {
+            scope_name
+                .components
+                .push_back(NameComponent::ident(method.clone()));
+            scope_name
+                .components
+                .push_back(NameComponent::interp(" callback"));
+        }
+    }
+
+    Some(scope_name)
+}
+
+/// Infers a name for a function passed as an argument to a class decorator `call`.
+///
+/// The function is named after the decorator and the decorated class, like
+/// `@connect arg in C` for `@connect(() => {}) class C {}`. The `path` leads
+/// from the root to the decorator call.
+fn infer_decorator_arg_name(call: &ast::CallExpr, path: &[Parent]) -> Option<ScopeName> {
+    let class_parent = match path {
+        [.., class_parent, Parent::Class(..), Parent::Decorator(..), Parent::Expr(..)] => {
+            class_parent
+        }
+        _ => return None,
+    };
+    let decorator = match &**call.callee.as_expr()? {
+        ast::Expr::Ident(ident) => ident,
+        ast::Expr::Member(member) => member.prop.as_ident()?,
+        _ => return None,
+    };
+    let class_ident = match class_parent {
+        Parent::ClassDecl(class_decl, _) => Some(&class_decl.ident),
+        Parent::ClassExpr(class_expr, _) => class_expr.ident.as_ref(),
+        _ => None,
+    };
+
+    let mut scope_name = ScopeName::new();
+    scope_name.components.push_back(NameComponent::interp("@"));
     scope_name
+        .components
+        .push_back(NameComponent::ident(decorator.clone()));
+    match class_ident {
+        Some(ident) => {
+            scope_name
+                .components
+                .push_back(NameComponent::interp(" arg in "));
+            scope_name
+                .components
+                .push_back(NameComponent::ident(ident.clone()));
+        }
+        None => scope_name
+            .components
+            .push_back(NameComponent::interp(" arg")),
+    }
+
+    Some(scope_name)
+}
+
+/// Infers the name of a function defined in the property descriptor passed to
+/// an `Object.defineProperty` `call`.
+///
+/// The `descriptor_path` leads from the call to the function. Getters and
+/// setters are prefixed accordingly, like `get X.foo`.
+fn infer_define_property_name(
+    call: &ast::CallExpr,
+    path: &[Parent],
+    descriptor_path: &[Parent],
+    options: &ParseOptions,
+) -> Option<ScopeName> {
+    let callee = call.callee.as_expr()?.as_member()?;
+    let is_define_property = matches!(&*callee.obj, ast::Expr::Ident(ident) if &*ident.sym == "Object")
+        && callee
+            .prop
+            .as_ident()
+            .is_some_and(|prop| &*prop.sym == "defineProperty");
+    if !is_define_property {
+        return None;
+    }
+
+    // the function has to be a direct property of the descriptor object literal
+    let mut parents = descriptor_path.iter().filter(|parent| {
+        !matches!(
+            parent,
+            Parent::ExprOrSpread(..)
+                | Parent::Expr(..)
+                | Parent::ParenExpr(..)
+                | Parent::PropOrSpread(..)
+                | Parent::Prop(..)
+        )
+    });
+    if !matches!(parents.next()?, Parent::ObjectLit(..)) {
+        return None;
+    }
+    let key = match parents.next()? {
+        Parent::KeyValueProp(kv, _) => &kv.key,
+        Parent::MethodProp(method, _) => &method.key,
+        _ => return None,
+    };
+    // and not be nested any deeper inside of it
+    if !parents.all(|parent| matches!(parent, Parent::FnExpr(..) | Parent::ArrowExpr(..))) {
+        return None;
+    }
+    let prefix = match &*key.as_ident()?.sym {
+        "get" => Some("get "),
+        "set" => Some("set "),
+        "value" => None,
+        _ => return None,
+    };
+
+    let prop = match call.args.get(1)?.expr.as_lit()? {
+        ast::Lit::Str(prop) => prop.value.to_string(),
+        _ => return None,
+    };
+
+    let mut scope_name = infer_name_from_expr(&call.args.first()?.expr, path, options)
+        .unwrap_or_else(ScopeName::new);
+    if !scope_name.components.is_empty() {
+        scope_name.components.push_back(NameComponent::interp("."));
+    }
+    scope_name.components.push_back(NameComponent::interp(prop));
+    if let Some(prefix) = prefix {
+        scope_name
+            .components
+            .push_front(NameComponent::interp(prefix));
+    }
+
+    Some(scope_name)
+}
+
+/// Returns the identifier of the hook if the `call` is a call to a React hook,
+/// such as `useCallback(...)` or `React.useEffect(...)`.
+///
+/// Hooks are recognized by their naming convention, `use` followed by an
+/// uppercase letter.
+fn react_hook_ident(callee: &ast::Expr) -> Option<ast::Ident> {
+    let ident = match callee {
+        ast::Expr::Ident(ident) => ident,
+        _ => member_callee_ident(callee)?,
+    };
+
+    let is_hook = ident
+        .sym
+        .strip_prefix("use")
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(|c| c.is_ascii_uppercase());
+
+    is_hook.then(|| ident.clone())
+}
+
+/// Returns the identifier of the method that is called by `callee`, like
+/// `method` in `obj.method()` or `obj?.method()`.
+fn member_callee_ident(callee: &ast::Expr) -> Option<&ast::Ident> {
+    let member = match callee {
+        ast::Expr::Member(member) => member,
+        ast::Expr::OptChain(opt_chain) => opt_chain.base.as_member()?,
+        _ => return None,
+    };
+    member.prop.as_ident()
+}
+
+/// Whether the argument number `arg_idx` is a spread argument, which does not
+/// correspond to a single parameter:
+/// `callee(...[() => {}])`
+fn is_spread_arg(args: &[ast::ExprOrSpread], arg_idx: usize) -> bool {
+    args.get(arg_idx).is_some_and(|arg| arg.spread.is_some())
+}
+
+/// Whether the `path` from a `return` statement or an arrow function body to a
+/// function or class only consists of the returned expression itself,
+/// optionally in parentheses.
+fn is_directly_returned(path: &[Parent]) -> bool {
+    path.iter().all(|parent| {
+        matches!(
+            parent,
+            Parent::BlockStmtOrExpr(..)
+                | Parent::Expr(..)
+                | Parent::ParenExpr(..)
+                | Parent::FnExpr(..)
+                | Parent::ClassExpr(..)
+        )
+    })
+}
+
+/// Infers the name of the function that the end of `path` is contained in.
+///
+/// Returns `None` if there is no such function, or its name could not be inferred.
+fn infer_enclosing_fn_name(path: &[Parent], options: &ParseOptions) -> Option<ScopeName> {
+    let fn_idx = path
+        .iter()
+        .rposition(|parent| matches!(parent, Parent::Function(..) | Parent::ArrowExpr(..)))?;
+    let fn_path = &path[..fn_idx];
+
+    let name = match path[fn_idx] {
+        Parent::Function(..) => {
+            let ident = match fn_path.last() {
+                Some(Parent::FnDecl(fn_decl, _)) => Some(fn_decl.ident.clone()),
+                Some(Parent::FnExpr(fn_expr, _)) => fn_expr.ident.clone(),
+                _ => None,
+            };
+            name_from_ident_or_ctx(ident, fn_path, options)
+        }
+        _ => infer_name_from_ctx(fn_path, options),
+    };
+
+    (!name.components.is_empty()).then_some(name)
+}
+
+/// Returns the identifier of the variable that the expression at the end of
+/// `path` directly initializes:
+/// `const $name = ...`
+fn declarator_ident(path: &[Parent]) -> Option<ast::Ident> {
+    for parent in path.iter().rev() {
+        match parent {
+            Parent::Expr(..)
+            | Parent::ParenExpr(..)
+            | Parent::OptChainExpr(..)
+            | Parent::OptChainBase(..) => {}
+            Parent::VarDeclarator(decl, _) => return decl.name.as_ident().map(|i| i.id.clone()),
+            _ => return None,
+        }
+    }
+    None
 }
 
 fn prefix_getters_setters(kind: ast::MethodKind, scope_name: &mut ScopeName) {
@@ -332,11 +1022,31 @@ fn prefix_getters_setters(kind: ast::MethodKind, scope_name: &mut ScopeName) {
 
 /// Returns a [`ScopeName`] corresponding to the given [`ast::Expr`].
 ///
-/// This is only possible if the expression is an identifier or a member expression.
-fn infer_name_from_expr(mut expr: &ast::Expr) -> Option<ScopeName> {
+/// This is only possible if the expression is an identifier or a member expression,
+/// which may be optionally chained or wrapped in parentheses.
+/// A leading CommonJS `module.exports` or `exports` is stripped from the name
+/// if configured in the `options`, which can result in an empty name.
+/// A leading `this` is replaced with the name of the class if the `path` of
+/// the expression is inside of a class method.
+fn infer_name_from_expr(
+    mut expr: &ast::Expr,
+    path: &[Parent],
+    options: &ParseOptions,
+) -> Option<ScopeName> {
     let mut scope_name = ScopeName::new();
 
     loop {
+        if options.strip_commonjs_exports && is_commonjs_exports(expr) {
+            if scope_name
+                .components
+                .front()
+                .is_some_and(|c| c.text() == ".")
+            {
+                scope_name.components.pop_front();
+            }
+            return Some(scope_name);
+        }
+
         match expr {
             ast::Expr::Ident(ident) => {
                 scope_name
@@ -346,24 +1056,34 @@ fn infer_name_from_expr(mut expr: &ast::Expr) -> Option<ScopeName> {
             }
 
             ast::Expr::Member(member) => {
-                if let Some(ident) = member.prop.as_ident() {
-                    scope_name
-                        .components
-                        .push_front(NameComponent::ident(ident.clone()));
-                    scope_name.components.push_front(NameComponent::interp("."));
-                }
+                push_member_prop(member, &mut scope_name)?;
+                expr = &member.obj;
+            }
 
-                if let Some(computed_prop) = member.prop.as_computed() {
-                    push_computed_prop_name(computed_prop, &mut scope_name)
+            // Optional chains and parentheses are walked through transparently:
+            // `a?.b` and `(a).b` are named just like `a.b`.
+            ast::Expr::OptChain(opt_chain) => match &*opt_chain.base {
+                ast::OptChainBase::Member(member) => {
+                    push_member_prop(member, &mut scope_name)?;
+                    expr = &member.obj;
                 }
-
-                expr = &member.obj;
+                ast::OptChainBase::Call(_) => return None,
+            },
+            ast::Expr::Paren(paren) => {
+                expr = &paren.expr;
+            }
+            // A sequence evaluates to its last expression, as emitted by minifiers:
+            // `(0, a).b`
+            ast::Expr::Seq(seq) => {
+                expr = seq.exprs.last()?;
             }
 
             ast::Expr::This(..) => {
-                scope_name
-                    .components
-                    .push_front(NameComponent::interp("this"));
+                let component = match enclosing_class_ident(path) {
+                    Some(ident) => NameComponent::ident(ident),
+                    None => NameComponent::interp("this"),
+                };
+                scope_name.components.push_front(component);
                 return Some(scope_name);
             }
 
@@ -372,20 +1092,114 @@ fn infer_name_from_expr(mut expr: &ast::Expr) -> Option<ScopeName> {
     }
 }
 
-fn push_computed_prop_name(prop_name: &ast::ComputedPropName, scope_name: &mut ScopeName) {
-    if let Some(literal) = prop_name.expr.as_lit() {
-        let component = NameComponent::interp(format!("[{}]", lit_as_string(literal)));
-        scope_name.components.push_front(component);
-    } else if let Some(ident) = prop_name.expr.as_ident() {
-        scope_name.components.push_front(NameComponent::interp("]"));
+/// Pushes the property of the given [`ast::MemberExpr`] to the front of `scope_name`.
+///
+/// Returns `None` if the property is computed from a dynamic expression.
+fn push_member_prop(member: &ast::MemberExpr, scope_name: &mut ScopeName) -> Option<()> {
+    if let Some(ident) = member.prop.as_ident() {
         scope_name
             .components
             .push_front(NameComponent::ident(ident.clone()));
-        scope_name.components.push_front(NameComponent::interp("["));
+        scope_name.components.push_front(NameComponent::interp("."));
+    }
+
+    if let Some(computed_prop) = member.prop.as_computed() {
+        push_computed_prop_name(computed_prop, scope_name)?;
+    }
+
+    Some(())
+}
+
+/// Returns the identifier of the class that `this` refers to at the end of `path`.
+///
+/// This is the case inside of class constructors, methods and property
+/// initializers, including any arrow functions nested in those.
+fn enclosing_class_ident(path: &[Parent]) -> Option<ast::Ident> {
+    let mut parents = path.iter().rev();
+
+    // find the nearest parent that binds `this`, skipping arrow functions
+    loop {
+        match parents.next()? {
+            Parent::Constructor(..) | Parent::ClassProp(..) | Parent::PrivateProp(..) => break,
+            Parent::Function(..) => match parents.next()? {
+                Parent::ClassMethod(..) | Parent::PrivateMethod(..) => break,
+                _ => return None,
+            },
+            _ => {}
+        }
+    }
+
+    for parent in parents {
+        match parent {
+            Parent::ClassDecl(class_decl, _) => return Some(class_decl.ident.clone()),
+            Parent::ClassExpr(class_expr, _) => return class_expr.ident.clone(),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether the given [`ast::Expr`] is the CommonJS `module.exports` or `exports` object.
+fn is_commonjs_exports(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::Ident(ident) => &*ident.sym == "exports",
+        ast::Expr::Member(member) => is_module_exports(member),
+        _ => false,
+    }
+}
+
+/// Whether the given [`ast::MemberExpr`] is `module.exports`.
+fn is_module_exports(member: &ast::MemberExpr) -> bool {
+    let is_module = matches!(&*member.obj, ast::Expr::Ident(ident) if &*ident.sym == "module");
+    let is_exports =
+        matches!(&member.prop, ast::MemberProp::Ident(ident) if &*ident.sym == "exports");
+
+    is_module && is_exports
+}
+
+/// Pushes a computed property with a literal or identifier key to the front of `scope_name`.
+///
+/// Returns `None` for any other expression, as its value is only known at runtime.
+fn push_computed_prop_name(
+    prop_name: &ast::ComputedPropName,
+    scope_name: &mut ScopeName,
+) -> Option<()> {
+    if let Some(literal) = prop_name.expr.as_lit() {
+        let component = NameComponent::key(format!("[{}]", lit_as_string(literal)));
+        scope_name.components.push_front(component);
     } else {
-        scope_name
-            .components
-            .push_front(NameComponent::interp("[<computed>]"));
+        let key_name = static_key_name(&prop_name.expr)?;
+        scope_name.components.push_front(NameComponent::interp("]"));
+        for component in key_name.components.into_iter().rev() {
+            scope_name.components.push_front(component);
+        }
+        scope_name.components.push_front(NameComponent::interp("["));
+    }
+
+    Some(())
+}
+
+/// Returns the name of a computed key that refers to a binding rather than
+/// computing a new value, like `[key]` or `[Symbol.iterator]`.
+fn static_key_name(expr: &ast::Expr) -> Option<ScopeName> {
+    match expr {
+        ast::Expr::Ident(ident) => {
+            let mut key_name = ScopeName::new();
+            key_name
+                .components
+                .push_back(NameComponent::ident(ident.clone()));
+            Some(key_name)
+        }
+        ast::Expr::Member(member) => {
+            let prop = member.prop.as_ident()?;
+            let mut key_name = static_key_name(&member.obj)?;
+            key_name.components.push_back(NameComponent::interp("."));
+            key_name
+                .components
+                .push_back(NameComponent::ident(prop.clone()));
+            Some(key_name)
+        }
+        _ => None,
     }
 }
 
@@ -404,9 +1218,31 @@ fn lit_as_string(lit: &ast::Lit) -> String {
 fn prop_name_to_component(prop: &ast::PropName) -> NameComponent {
     match prop {
         ast::PropName::Ident(ref i) => NameComponent::ident(i.clone()),
-        ast::PropName::Str(s) => NameComponent::interp(format!("<\"{}\">", s.value)),
-        ast::PropName::Num(n) => NameComponent::interp(format!("<{}>", n)),
+        // String keys are only quoted if they are not valid identifiers:
+        // `{ "quoted"() {} }` => `quoted`
+        // `{ "do-thing"() {} }` => `["do-thing"]`
+        // Unquoted keys point to the string contents, without the quotes.
+        ast::PropName::Str(s) if is_valid_ident(&s.value) => {
+            let span = s
+                .span
+                .with_lo(s.span.lo + BytePos(1))
+                .with_hi(s.span.hi - BytePos(1));
+            NameComponent::ident(ast::Ident::new(s.value.clone(), span))
+        }
+        ast::PropName::Str(s) => NameComponent::key(format!("[\"{}\"]", s.value)),
+        ast::PropName::Num(n) => NameComponent::key(format!("[{}]", n)),
         ast::PropName::Computed(_) => NameComponent::interp("<computed>"),
-        ast::PropName::BigInt(i) => NameComponent::interp(format!("<{}n>", i.value)),
+        ast::PropName::BigInt(i) => NameComponent::key(format!("[{}n]", i.value)),
     }
 }
+
+/// Whether the `s` can be used as an identifier, like in a `obj.$s` member access.
+fn is_valid_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+
+    chars
+        .next()
+        .is_some_and(|c| is_ident_char(c) && !c.is_ascii_digit())
+        && chars.all(is_ident_char)
+}
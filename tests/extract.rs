@@ -1,4 +1,11 @@
-use js_source_scopes::{extract_scope_names, Scopes};
+use js_source_scopes::{
+    collect_scopes_from_module, collect_scopes_from_script, extract_export_aliases,
+    extract_export_aliases_with_options, extract_named_scopes, extract_named_scopes_with_options,
+    extract_scope_names, extract_scope_names_at, extract_scope_names_at_with_options,
+    extract_scope_names_with_options, for_each_scope_name, ParseOptions, ScopeKind, Scopes,
+};
+use swc_common::BytePos;
+use swc_ecma_parser::{EsConfig, Parser, StringInput, Syntax};
 
 fn scope_strs(scopes: Scopes) -> Vec<Option<String>> {
     scopes
@@ -166,9 +173,9 @@ fn extract_object_weird_properties() {
 
     let expected = [
         Some("a.<computed>".into()),
-        Some("a.<1.7>".into()),
-        Some("a.<\"bar\">".into()),
-        Some("a.<1n>".into()),
+        Some("a[1.7]".into()),
+        Some("a.bar".into()),
+        Some("a[1n]".into()),
     ];
     assert_eq!(scopes, expected);
 }
@@ -193,6 +200,31 @@ fn extract_named_class_expr() {
     assert_eq!(scopes, expected);
 }
 
+#[test]
+fn extract_anonymous_class_expr() {
+    let src = r#"
+        const Widget = class {
+            render() {}
+        };
+        const obj = {
+            Widget: class {
+                render() {}
+            },
+        };
+        "#;
+
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("new Widget".into()),
+        Some("Widget.render".into()),
+        Some("new obj.Widget".into()),
+        Some("obj.Widget.render".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
 #[test]
 fn extract_anon_obj_literal() {
     let src = r#"
@@ -262,6 +294,7 @@ fn extract_computed_properties() {
         Klass.prototype["method"] = () => {}
         Klass.prototype[method] = () => {}
         Klass.prototype[1 + 1] = () => {};
+        Klass.prototype[Symbol.iterator] = function () {};
         "#;
     let scopes = extract_scope_names(src).unwrap();
     let scopes = scope_strs(scopes);
@@ -270,7 +303,1286 @@ fn extract_computed_properties() {
         Some("Klass.prototype[42]".into()),
         Some("Klass.prototype[\"method\"]".into()),
         Some("Klass.prototype[method]".into()),
-        Some("Klass.prototype[<computed>]".into()),
+        None,
+        Some("Klass.prototype[Symbol.iterator]".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_script_top_level_return() {
+    let src = r#"
+        function script_fn() {}
+        if (window.loaded) {
+            return;
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [Some("script_fn".into())];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_function_body() {
+    // the body of a `new Function("...")` or a worker blob, without the wrapper
+    let src = r#"
+        "use strict";
+        var self = this;
+        function helper(data) {}
+        self.onmessage = function (e) {
+            return helper(e.data);
+        };
+        return helper;
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [Some("helper".into()), Some("self.onmessage".into())];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_script_await_identifier() {
+    // `await` is a reserved word in modules, so this only parses as a script
+    let src = r#"
+        var await = 1;
+        function script_fn() {}
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [Some("script_fn".into())];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_script_with_statement() {
+    let src = r#"
+        with (Math) {
+            var with_fn = function () {
+                return max(1, 2);
+            };
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [Some("with_fn".into())];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_module_top_level_await() {
+    let src = r#"
+        import { load } from "./load.js";
+        const config = await load();
+        export async function tla_fn() {
+            await config.ready;
+        }
+        for await (const chunk of config.stream) {
+            chunk.forEach((x) => x);
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [Some("tla_fn".into()), None];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn infer_from_sequence_expressions() {
+    let src = r#"
+        const seq_fn = (init(), function () {});
+        const seq_arrow = (0, () => {});
+        const not_last = (function () {}, other);
+        (0, obj.prop).method = function () {};
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("seq_fn".into()),
+        Some("seq_arrow".into()),
+        None,
+        Some("obj.prop.method".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_optional_catch_binding() {
+    let src = r#"
+        function outer() {
+            try {
+                risky();
+            } catch {
+                const fallback = () => {};
+            }
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [Some("outer".into()), Some("fallback".into())];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_spread_and_rest() {
+    let src = r#"
+        foo(...[() => {}]);
+        useEffect(...[() => {}, []]);
+        const spread = { ...{ inner: () => {} } };
+        function rest(...[first = () => {}]) {}
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        None,
+        None,
+        Some("spread.inner".into()),
+        Some("rest".into()),
+        Some("first".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn infer_from_iife() {
+    let src = r#"
+        const iife_fn = (function () {})();
+        const iife_arrow = (() => {})();
+        const not_iife = wrap(function () {});
+        const doubled = items.map((x) => x * 2);
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    // only the callee is named, arguments stay anonymous
+    let expected = [
+        Some("iife_fn".into()),
+        Some("iife_arrow".into()),
+        None,
+        None,
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_commonjs_exports() {
+    let src = r#"
+        module.exports = function () {};
+        module.exports.handler = function () {};
+        exports.foo = () => {};
+        exports.deep.bar = () => {};
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("default".into()),
+        Some("handler".into()),
+        Some("foo".into()),
+        Some("deep.bar".into()),
+    ];
+    assert_eq!(scopes, expected);
+
+    let mut options = ParseOptions::default();
+    options.strip_commonjs_exports = false;
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("module.exports".into()),
+        Some("module.exports.handler".into()),
+        Some("exports.foo".into()),
+        Some("exports.deep.bar".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn scope_name_to_string() {
+    let src = r#"
+        function single() {}
+        deep.assigned.klass = class {};
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+
+    for (_range, name) in scopes {
+        let name = name.unwrap();
+        assert_eq!(name.as_components_str(), name.to_string());
+        assert_eq!(String::from(&name), name.to_string());
+    }
+}
+
+#[test]
+fn infer_from_react_hooks() {
+    let src = r#"
+        function Component() {
+            const handler = useCallback(() => {}, []);
+            const value = React.useMemo(function () {}, []);
+            useEffect(() => {}, []);
+            user(() => {});
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("Component".into()),
+        Some("handler".into()),
+        Some("value".into()),
+        Some("useEffect callback".into()),
+        None,
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn infer_callback_names() {
+    let src = r#"
+        el.addEventListener("click", function () {});
+        items.map((x) => x);
+        const doubled = items.map((x) => x * 2);
+        wrap(() => {});
+        fetch(url).then(function () {}).catch(() => {}).finally(() => {});
+        query.where("a").orderBy("b", (x) => x);
+        setTimeout(() => {}, 100);
+        window.setInterval(function () {}, 100);
+        requestAnimationFrame(() => {});
+        schedule(() => {}, 100);
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        None, None, None, None, None, None, None, None, None, None, None, None,
+    ];
+    assert_eq!(scopes, expected);
+
+    let mut options = ParseOptions::default();
+    options.infer_callback_names = true;
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("<click handler>".into()),
+        Some("map callback".into()),
+        Some("doubled".into()),
+        None,
+        Some("then callback".into()),
+        Some("catch callback".into()),
+        Some("finally callback".into()),
+        Some("orderBy callback".into()),
+        Some("setTimeout callback".into()),
+        Some("setInterval callback".into()),
+        Some("requestAnimationFrame callback".into()),
+        None,
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn infer_default_export_class() {
+    let src = r#"
+        const Foo = class {};
+        export default class {
+            method() {}
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("new Foo".into()),
+        Some("new default".into()),
+        Some("default.method".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn infer_default_export_fn() {
+    let src = r#"
+        export default function () {}
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [Some("default".into())];
+    assert_eq!(scopes, expected);
+
+    let src = r#"
+        export default class Named {
+            method() {}
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [Some("new Named".into()), Some("Named.method".into())];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn infer_this_assignments() {
+    let src = r#"
+        class Foo {
+            constructor() {
+                this.handler = () => {};
+            }
+            method() {
+                this.callback = function () {};
+            }
+        }
+        function Bar() {
+            this.baz = () => {};
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("new Foo".into()),
+        Some("Foo.handler".into()),
+        Some("Foo.method".into()),
+        Some("Foo.callback".into()),
+        Some("Bar".into()),
+        Some("this.baz".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn collect_from_parsed() {
+    let src = r#"
+        function fn_decl() {}
+        const arrow = () => {};
+        "#;
+    let new_parser = || {
+        let input = StringInput::new(src, BytePos(0), BytePos(src.len() as u32));
+        Parser::new(Default::default(), input, None)
+    };
+    let expected = scope_strs(extract_scope_names(src).unwrap());
+
+    let module = new_parser().parse_module().unwrap();
+    let scopes = collect_scopes_from_module(&module, &ParseOptions::default());
+    assert_eq!(scope_strs(scopes), expected);
+
+    let script = new_parser().parse_script().unwrap();
+    let scopes = collect_scopes_from_script(&script, &ParseOptions::default());
+    assert_eq!(scope_strs(scopes), expected);
+}
+
+#[test]
+fn infer_from_tagged_template() {
+    let src = r#"
+        const Button = styled.div`
+            color: ${(props) => props.color};
+        `;
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [Some("Button".into())];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn infer_from_destructuring_defaults() {
+    let src = r#"
+        const { onClick = () => {} } = props;
+        const [cb = function () {}] = arr;
+        function with_default(callback = () => {}) {}
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("onClick".into()),
+        Some("cb".into()),
+        Some("with_default".into()),
+        Some("callback".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_with_decorators() {
+    let src = r#"
+        @decorator
+        class Decorated {
+            @observable
+            method() {}
+        }
+        "#;
+    let mut options = ParseOptions::default();
+    options.syntax = Syntax::Es(EsConfig {
+        decorators: true,
+        ..Default::default()
+    });
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("new Decorated".into()),
+        Some("Decorated.method".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn infer_decorator_arg_names() {
+    let src = r#"
+        @connect((state) => state.foo)
+        class Connected {
+            render() {}
+        }
+        @redux.connect(function () {})
+        class Other {}
+        "#;
+    let mut options = ParseOptions::default();
+    options.syntax = Syntax::Es(EsConfig {
+        decorators: true,
+        ..Default::default()
+    });
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("new Connected".into()),
+        None,
+        Some("Connected.render".into()),
+        Some("new Other".into()),
+        None,
+    ];
+    assert_eq!(scopes, expected);
+
+    options.infer_callback_names = true;
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("new Connected".into()),
+        Some("@connect arg in Connected".into()),
+        Some("Connected.render".into()),
+        Some("new Other".into()),
+        Some("@connect arg in Other".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_class_prefix() {
+    let src = r#"
+        class Foo {
+            method() {}
+        }
+        const Bar = class {};
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("new Foo".into()),
+        Some("Foo.method".into()),
+        Some("new Bar".into()),
+    ];
+    assert_eq!(scopes, expected);
+
+    let mut options = ParseOptions::default();
+    options.class_prefix = "";
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("Foo".into()),
+        Some("Foo.method".into()),
+        Some("Bar".into()),
+    ];
+    assert_eq!(scopes, expected);
+
+    options.class_prefix = "class ";
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("class Foo".into()),
+        Some("Foo.method".into()),
+        Some("class Bar".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_aliased_exports() {
+    let src = r#"
+        function foo() {}
+        const baz = () => {};
+        function kept() {}
+        export { foo as bar, baz as default, kept };
+        export { other as renamed } from "./other.js";
+        "#;
+
+    // the scopes are still named after their declaration
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+    let expected = [Some("foo".into()), Some("baz".into()), Some("kept".into())];
+    assert_eq!(scopes, expected);
+
+    let aliases = extract_export_aliases(src).unwrap();
+    let expected = [
+        (String::from("foo"), String::from("bar")),
+        (String::from("baz"), String::from("default")),
+    ];
+    assert_eq!(aliases, expected);
+
+    assert!(extract_export_aliases("function foo() {}")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn extract_export_aliases_with_decorators() {
+    let src = r#"
+        @decorator
+        class Foo {}
+        export { Foo as Bar };
+        "#;
+
+    let mut options = ParseOptions::default();
+    options.syntax = Syntax::Es(EsConfig {
+        decorators: true,
+        ..Default::default()
+    });
+    let aliases = extract_export_aliases_with_options(src, &options).unwrap();
+    assert_eq!(aliases, [(String::from("Foo"), String::from("Bar"))]);
+
+    assert!(extract_export_aliases(src).is_err());
+}
+
+#[test]
+fn extract_parse_error() {
+    let src = "let x = ;";
+    let err = extract_scope_names(src).unwrap_err();
+
+    assert_eq!(err.range(), 8..9);
+    assert!(!err.message().is_empty());
+    assert_eq!(err.to_string(), format!("8:9:{}", err.message()));
+}
+
+#[test]
+fn scope_name_truncated() {
+    let src = r#"
+        a.b.c.D = class {};
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let name = scopes[0].1.as_ref().unwrap();
+
+    assert_eq!(name.to_truncated_string(10), "new a.b.c.D");
+    assert_eq!(name.to_truncated_string(4), "new a.b.c.D");
+    assert_eq!(name.to_truncated_string(2), "new ….c.D");
+    assert_eq!(name.to_truncated_string(1), "new ….D");
+}
+
+#[test]
+fn extract_nested_obj_literal_methods() {
+    let src = r#"
+        const two_levels = {
+            outer: {
+                inner() {},
+            },
+        };
+        const three_levels = {
+            a: {
+                b: {
+                    c() {},
+                    d: () => {},
+                },
+            },
+        };
+        ({
+            outer: {
+                inner() {},
+            },
+        });
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("two_levels.outer.inner".into()),
+        Some("three_levels.a.b.c".into()),
+        Some("three_levels.a.b.d".into()),
+        Some("<object>.outer.inner".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_outline() {
+    let src = r#"
+        class Foo {
+            constructor() {}
+            bar() {
+                return function () {};
+            }
+            static baz() {}
+        }
+        function free() {
+            function nested() {}
+        }
+        [1, 2].map(() => {});
+        const arrow = () => {};
+        "#;
+    let outline = extract_named_scopes(src).unwrap();
+
+    let names: Vec<_> = outline.iter().map(|(_, name)| name.as_str()).collect();
+    let expected = ["new Foo", "Foo.bar", "Foo.baz", "free", "nested", "arrow"];
+    assert_eq!(names, expected);
+
+    assert!(outline
+        .windows(2)
+        .all(|pair| pair[0].0.start <= pair[1].0.start));
+    let foo = &outline[0].0;
+    assert!(outline[1..3]
+        .iter()
+        .all(|(range, _)| foo.start <= range.start && range.end <= foo.end));
+}
+
+#[test]
+fn extract_outline_with_options() {
+    let src = r#"
+        @decorator
+        class Foo {
+            bar() {}
+        }
+        items.map(() => {});
+        "#;
+
+    let mut options = ParseOptions::default();
+    options.syntax = Syntax::Es(EsConfig {
+        decorators: true,
+        ..Default::default()
+    });
+    options.infer_callback_names = true;
+    options.class_prefix = "";
+    let outline = extract_named_scopes_with_options(src, &options).unwrap();
+
+    let names: Vec<_> = outline.iter().map(|(_, name)| name.as_str()).collect();
+    assert_eq!(names, ["Foo", "Foo.bar", "map callback"]);
+
+    assert!(extract_named_scopes(src).is_err());
+}
+
+#[test]
+fn extract_returned_function_names() {
+    let src = r#"
+        function makeHandler() {
+            return function () {};
+        }
+        function makeArrow() {
+            if (true) {
+                return (() => {});
+            }
+        }
+        const makeNamed = () => {
+            return function named() {};
+        };
+        function notDirect() {
+            return [() => {}];
+        }
+        "#;
+
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+    let expected = [
+        Some("makeHandler".into()),
+        None,
+        Some("makeArrow".into()),
+        None,
+        Some("makeNamed".into()),
+        Some("named".into()),
+        Some("notDirect".into()),
+        None,
+    ];
+    assert_eq!(scopes, expected);
+
+    let mut options = ParseOptions::default();
+    options.infer_returned_function_names = true;
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+    let expected = [
+        Some("makeHandler".into()),
+        Some("makeHandler.<return>".into()),
+        Some("makeArrow".into()),
+        Some("makeArrow.<return>".into()),
+        Some("makeNamed".into()),
+        Some("makeNamed.named".into()),
+        Some("notDirect".into()),
+        None,
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_curried_arrow_names() {
+    let src = r#"
+        const add = (a) => (b) => (c) => a + b + c;
+        const withBlock = (a) => {
+            return (b) => a + b;
+        };
+        const notDirect = (a) => [(b) => a + b];
+        "#;
+
+    // curried arrows are named by default
+    let scopes = extract_scope_names_with_options(src, &ParseOptions::default()).unwrap();
+    let scopes = scope_strs(scopes);
+    let expected = [
+        Some("add".into()),
+        Some("add.<return>".into()),
+        Some("add.<return>.<return>".into()),
+        Some("withBlock".into()),
+        None,
+        Some("notDirect".into()),
+        None,
+    ];
+    assert_eq!(scopes, expected);
+
+    let mut options = ParseOptions::default();
+    options.infer_returned_function_names = true;
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+    let expected = [
+        Some("add".into()),
+        Some("add.<return>".into()),
+        Some("add.<return>.<return>".into()),
+        Some("withBlock".into()),
+        Some("withBlock.<return>".into()),
+        Some("notDirect".into()),
+        None,
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_optional_chain_member_names() {
+    let src = r#"
+        (foo?.bar).baz = function () {};
+        (foo?.bar?.[0]).qux = () => {};
+        ((foo).bar).quux = () => {};
+        class Button {
+            constructor() {
+                (this.props?.handlers).onClick = () => {};
+            }
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("foo.bar.baz".into()),
+        Some("foo.bar[0].qux".into()),
+        Some("foo.bar.quux".into()),
+        Some("new Button".into()),
+        Some("Button.props.handlers.onClick".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn infer_optional_call_callback_names() {
+    let src = r#"
+        this.props?.onClick(() => {});
+        a?.b.map(() => {});
+        maybe?.(() => {});
+        React?.useEffect(() => {});
+        const handler = items?.find((x) => x);
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [None, None, None, Some("useEffect callback".into()), None];
+    assert_eq!(scopes, expected);
+
+    let mut options = ParseOptions::default();
+    options.infer_callback_names = true;
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("onClick callback".into()),
+        Some("map callback".into()),
+        None,
+        Some("useEffect callback".into()),
+        Some("handler".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn scope_name_into_component_strings() {
+    let src = r#"
+        class Foo {
+            get bar() {}
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+
+    for (_, name) in scopes {
+        let name = name.unwrap();
+        let display = name.to_string();
+        let components = name.into_component_strings();
+        assert_eq!(components.concat(), display);
+    }
+
+    let name = extract_scope_names("a.b = () => {}").unwrap().remove(0).1;
+    let components = name.unwrap().into_component_strings();
+    assert_eq!(components, ["a", ".", "b"]);
+}
+
+#[test]
+fn extract_define_property_accessors() {
+    let src = r#"
+        Object.defineProperty(X, "foo", {
+            get: function () {},
+            set: function (v) {},
+        });
+        Object.defineProperty(X.prototype, "bar", { get() {} });
+        Object.defineProperty(exports, "baz", {
+            enumerable: true,
+            get: function () {},
+        });
+        "#;
+
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+    let expected = [
+        Some("<object>.get".into()),
+        Some("<object>.set".into()),
+        Some("<object>.get".into()),
+        Some("<object>.get".into()),
+    ];
+    assert_eq!(scopes, expected);
+
+    let mut options = ParseOptions::default();
+    options.infer_callback_names = true;
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+    let expected = [
+        Some("get X.foo".into()),
+        Some("set X.foo".into()),
+        Some("get X.prototype.bar".into()),
+        Some("get baz".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_define_property_nested_closures() {
+    let src = r#"
+        Object.defineProperty(X, "foo", {
+            get: function () {
+                return this.items.map(function (item) {});
+            },
+            value: function () {
+                ({ inner() {} });
+            },
+        });
+        "#;
+
+    let mut options = ParseOptions::default();
+    options.infer_callback_names = true;
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let scopes = scope_strs(scopes);
+
+    // only the descriptor functions themselves are named after the property
+    let expected = [
+        Some("get X.foo".into()),
+        Some("map callback".into()),
+        Some("X.foo".into()),
+        Some("<object>.inner".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn for_each_scope_name_matches_extract() {
+    let src = r#"
+        class Foo {
+            bar() {
+                return [1, 2].map(() => {});
+            }
+        }
+        export default function () {}
+        exports.handler = async function () {};
+        (function () {})();
+        "#;
+
+    let options = ParseOptions::default();
+    let mut streamed = vec![];
+    for_each_scope_name(src, &options, |range, name, _info| {
+        streamed.push((range, name.map(|n| n.to_string())));
+    })
+    .unwrap();
+
+    let scopes = extract_scope_names_with_options(src, &options).unwrap();
+    let collected: Vec<_> = scopes
+        .into_iter()
+        .map(|(range, name)| (range, name.map(|n| n.to_string())))
+        .collect();
+
+    assert_eq!(streamed.len(), 6);
+    assert_eq!(streamed, collected);
+
+    assert!(for_each_scope_name("function (", &options, |_, _, _| {}).is_err());
+}
+
+#[test]
+fn extract_string_and_number_keys() {
+    let src = r#"
+        const obj = {
+            "do-thing": function () {},
+            0: () => {},
+            "quoted"() {},
+            get "x-y"() {},
+        };
+        class K {
+            "do-thing"() {}
+            42() {}
+        }
+        ({ "do-thing"() {} });
+        "#;
+
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("obj[\"do-thing\"]".into()),
+        Some("obj[0]".into()),
+        Some("obj.quoted".into()),
+        Some("get obj[\"x-y\"]".into()),
+        Some("new K".into()),
+        Some("K[\"do-thing\"]".into()),
+        Some("K[42]".into()),
+        Some("<object>[\"do-thing\"]".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_quoted_keys_as_identifiers() {
+    let quoted_src = r#"class Foo { "quoted"() {} }"#;
+    let ident_src = r#"class Foo { quoted() {} }"#;
+
+    let quoted = extract_scope_names(quoted_src).unwrap();
+    let ident = extract_scope_names(ident_src).unwrap();
+    let quoted = quoted[1].1.as_ref().unwrap();
+    let ident = ident[1].1.as_ref().unwrap();
+
+    assert_eq!(quoted.to_string(), "Foo.quoted");
+    assert_eq!(quoted.leaf(), Some("quoted"));
+    assert_eq!(quoted.to_truncated_string(1), "….quoted");
+    assert!(quoted.semantic_eq(ident));
+
+    // the key points to the string contents, without the quotes
+    let key = quoted.components().last().unwrap();
+    let range = key.range().unwrap();
+    assert_eq!(
+        &quoted_src[range.start as usize..range.end as usize],
+        "quoted"
+    );
+}
+
+#[test]
+fn extract_accessor_separators() {
+    let src = r#"
+        class A {
+            static get foo() {}
+            get #bar() {}
+            set #bar(v) {}
+        }
+        const B = class {
+            get baz() {}
+        };
+        const o = {
+            inner: {
+                get qux() {
+                    return () => {};
+                },
+                set [key](v) {},
+            },
+        };
+        ({ get anon() {} });
+        "#;
+
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("new A".into()),
+        Some("get A.foo".into()),
+        Some("get A.#bar".into()),
+        Some("set A.#bar".into()),
+        Some("new B".into()),
+        Some("get B.baz".into()),
+        Some("get o.inner.qux".into()),
+        None,
+        Some("set o.inner.<computed>".into()),
+        Some("get anon".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_class_field_initializers() {
+    let src = r#"
+        class X {
+            handler = () => {};
+            static create = function () {};
+            #private = () => {};
+            nested = {
+                method() {},
+            };
+        }
+        const Y = class {
+            onClick = () => {};
+        };
+        "#;
+
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("new X".into()),
+        Some("X.handler".into()),
+        Some("X.create".into()),
+        Some("X.#private".into()),
+        Some("X.nested.method".into()),
+        Some("new Y".into()),
+        Some("Y.onClick".into()),
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_scope_kinds() {
+    let src = r#"
+        function decl() {}
+        const arrow = () => {};
+        class K {
+            method() {}
+            #private() {}
+            get getter() {}
+            set setter(v) {}
+            static {
+                init();
+            }
+        }
+        const obj = {
+            method() {},
+            get getter() {},
+            set setter(v) {},
+            fn: function () {},
+        };
+        "#;
+
+    let mut kinds = vec![];
+    for_each_scope_name(src, &ParseOptions::default(), |_range, name, info| {
+        kinds.push((name.unwrap().to_string(), info.kind));
+    })
+    .unwrap();
+
+    let expected = [
+        ("decl", ScopeKind::Function),
+        ("arrow", ScopeKind::Arrow),
+        ("new K", ScopeKind::Class),
+        ("K.method", ScopeKind::Method),
+        ("K.#private", ScopeKind::Method),
+        ("get K.getter", ScopeKind::Getter),
+        ("set K.setter", ScopeKind::Setter),
+        ("K.<static>", ScopeKind::StaticBlock),
+        ("obj.method", ScopeKind::Method),
+        ("get obj.getter", ScopeKind::Getter),
+        ("set obj.setter", ScopeKind::Setter),
+        ("obj.fn", ScopeKind::Function),
+    ];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(name, kind)| (name.to_string(), kind))
+        .collect();
+    assert_eq!(kinds, expected);
+}
+
+#[test]
+fn extract_leading_pragmas() {
+    let src = r#"
+        /*#__NO_SIDE_EFFECTS__*/ function decl() {}
+        function plain() {}
+        const pure = /* @__PURE__ */ () => {};
+        const other = /* some comment */ () => {};
+        export const exported = /*#__NO_SIDE_EFFECTS__*/ function () {};
+        "#;
+
+    let mut pragmas = vec![];
+    for_each_scope_name(src, &ParseOptions::default(), |_range, name, info| {
+        pragmas.push((name.unwrap().to_string(), info.has_leading_pragma));
+    })
+    .unwrap();
+
+    let expected = [
+        ("decl", true),
+        ("plain", false),
+        ("pure", true),
+        ("other", false),
+        ("exported", true),
+    ];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(name, pragma)| (name.to_string(), pragma))
+        .collect();
+    assert_eq!(pragmas, expected);
+}
+
+#[test]
+fn extract_at_base_offset() {
+    let prefix = "const unchanged = () => {};\nfunction alsoUnchanged() {}\n";
+    let region = r#"
+        class Changed {
+            method() {}
+        }
+        obj.prop = function () {};
+        "#;
+    let full = format!("{prefix}{region}");
+
+    let base_offset = prefix.len() as u32;
+    let region_scopes = extract_scope_names_at(region, base_offset).unwrap();
+    let full_scopes = extract_scope_names(&full).unwrap();
+
+    let with_component_ranges = |scopes: Scopes| -> Vec<_> {
+        scopes
+            .into_iter()
+            .map(|(range, name)| {
+                let components: Vec<_> = name
+                    .unwrap()
+                    .components()
+                    .map(|c| (c.text().to_string(), c.range()))
+                    .collect();
+                (range, components)
+            })
+            .collect()
+    };
+    let region_scopes = with_component_ranges(region_scopes);
+    let full_scopes = with_component_ranges(full_scopes);
+
+    assert_eq!(region_scopes.len(), 3);
+    assert_eq!(region_scopes, full_scopes[2..]);
+    for (range, components) in &region_scopes {
+        assert!(range.start >= base_offset);
+        for (text, range) in components {
+            if let Some(range) = range {
+                assert_eq!(&full[range.start as usize..range.end as usize], text);
+            }
+        }
+    }
+
+    let err = extract_scope_names_at("let x = ;", 1).unwrap_err();
+    assert_eq!(err.range(), 9..10);
+
+    assert!(extract_scope_names_at("function foo() {}", u32::MAX - 5).is_err());
+}
+
+#[test]
+fn extract_at_base_offset_with_options() {
+    let prefix = "const unchanged = () => {};\n";
+    let region = r#"
+        @decorator
+        class Changed {}
+        items.map(() => {});
+        "#;
+    let full = format!("{prefix}{region}");
+
+    let mut options = ParseOptions::default();
+    options.syntax = Syntax::Es(EsConfig {
+        decorators: true,
+        ..Default::default()
+    });
+    options.infer_callback_names = true;
+    options.class_prefix = "";
+
+    let base_offset = prefix.len() as u32;
+    let region_scopes = extract_scope_names_at_with_options(region, base_offset, &options).unwrap();
+    let full_scopes = extract_scope_names_with_options(&full, &options).unwrap();
+
+    let with_ranges = |scopes: Scopes| -> Vec<_> {
+        scopes
+            .into_iter()
+            .map(|(range, name)| (range, name.map(|n| n.to_string())))
+            .collect()
+    };
+    let region_scopes = with_ranges(region_scopes);
+    let full_scopes = with_ranges(full_scopes);
+
+    assert_eq!(
+        region_scopes,
+        [
+            (37..72, Some("Changed".into())),
+            (91..99, Some("map callback".into())),
+        ]
+    );
+    assert_eq!(region_scopes, full_scopes[1..]);
+
+    // decorators are a syntax error without the options
+    assert!(extract_scope_names_at(region, base_offset).is_err());
+}
+
+#[test]
+fn extract_nested_computed_assignments() {
+    let src = r#"
+        handlers["click"].primary = function () {};
+        handlers[0][KEY] = () => {};
+        handlers[getKey()].fallback = () => {};
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("handlers[\"click\"].primary".into()),
+        Some("handlers[0][KEY]".into()),
+        None,
+    ];
+    assert_eq!(scopes, expected);
+}
+
+#[test]
+fn extract_through_labels_and_blocks() {
+    let src = r#"
+        function named() {
+            outer: for (const item of items) {
+                {
+                    const inLoop = () => {};
+                    items.forEach(() => {});
+                }
+                if (item) continue outer;
+            }
+        }
+        label: {
+            var inBlock = function () {};
+        }
+        "#;
+    let scopes = extract_scope_names(src).unwrap();
+    let scopes = scope_strs(scopes);
+
+    let expected = [
+        Some("named".into()),
+        Some("inLoop".into()),
+        None,
+        Some("inBlock".into()),
     ];
     assert_eq!(scopes, expected);
 }
@@ -29,6 +29,58 @@ fn resolve_original_scopes(
     resolved_scopes.collect()
 }
 
+#[test]
+fn converts_component_span() {
+    let src = "const a = 1;\nclass Klass {}";
+
+    let scopes = extract_scope_names(src).unwrap();
+    let name = scopes[0].1.as_ref().unwrap();
+
+    let ctx = SourceContext::new(src).unwrap();
+
+    let components: Vec<_> = name.components().map(|c| ctx.component_span(c)).collect();
+    assert_eq!(
+        components,
+        [
+            None,
+            Some((SourcePosition::new(1, 6), SourcePosition::new(1, 11)))
+        ]
+    );
+}
+
+#[test]
+fn converts_scope_ranges() {
+    let src = "function multiLine() {\n  return 1;\n}\nconst last = () => {}";
+
+    let scopes = extract_scope_names(src).unwrap();
+    let ctx = SourceContext::new(src).unwrap();
+
+    let spans: Vec<_> = scopes
+        .into_iter()
+        .map(|(range, _)| ctx.range_to_positions(range))
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            Some((SourcePosition::new(0, 0), SourcePosition::new(2, 1))),
+            // the arrow function ends at the very end of the source
+            Some((SourcePosition::new(3, 13), SourcePosition::new(3, 21))),
+        ]
+    );
+
+    // empty ranges
+    assert_eq!(
+        ctx.range_to_positions(9..9),
+        Some((SourcePosition::new(0, 9), SourcePosition::new(0, 9)))
+    );
+    let end = src.len() as u32;
+    assert_eq!(
+        ctx.range_to_positions(end..end),
+        Some((SourcePosition::new(3, 21), SourcePosition::new(3, 21)))
+    );
+    assert_eq!(ctx.range_to_positions(end..end + 1), None);
+}
+
 #[test]
 fn resolves_scopes_simple() {
     let minified = std::fs::read_to_string("tests/fixtures/simple/minified.js").unwrap();
@@ -232,3 +284,43 @@ fn should_resolve_exactish() {
     assert_eq!(resolved_scopes[3].2, Some("invoke".into()));
     assert_eq!(resolved_scopes[4].2, Some("test".into()));
 }
+
+#[test]
+fn resolves_names_from_tokens() {
+    let minified = "var a={b:function(){}};";
+    // `a` maps to a token with the name `originalObj`, `b` to a token without a name
+    let map = r#"{
+        "version": 3,
+        "sources": ["original.js"],
+        "names": ["originalObj"],
+        "mappings": "IAAIA,GAAG"
+    }"#;
+
+    let scopes = extract_scope_names(minified).unwrap();
+
+    let resolved_scopes = resolve_original_scopes(minified, map, scopes);
+
+    assert_eq!(resolved_scopes.len(), 1);
+    assert_eq!(resolved_scopes[0].1, Some("a.b".into()));
+    assert_eq!(resolved_scopes[0].2, Some("originalObj.b".into()));
+}
+
+#[test]
+fn resolves_mangled_property_names() {
+    let minified = "obj.a=function(){};";
+    // both `obj` and the mangled property `a` map to tokens with a name
+    let map = r#"{
+        "version": 3,
+        "sources": ["original.js"],
+        "names": ["originalObj", "handler"],
+        "mappings": "AAAAA,IAAIC"
+    }"#;
+
+    let scopes = extract_scope_names(minified).unwrap();
+
+    let resolved_scopes = resolve_original_scopes(minified, map, scopes);
+
+    assert_eq!(resolved_scopes.len(), 1);
+    assert_eq!(resolved_scopes[0].1, Some("obj.a".into()));
+    assert_eq!(resolved_scopes[0].2, Some("originalObj.handler".into()));
+}